@@ -0,0 +1,10 @@
+CREATE TABLE IF NOT EXISTS wallet_balances (
+    id BIGSERIAL PRIMARY KEY,
+    timestamp TIMESTAMPTZ NOT NULL,
+    wallet_address TEXT NOT NULL,
+    token_symbol TEXT NOT NULL,
+    token_address TEXT,
+    balance TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_wallet_balances_timestamp ON wallet_balances(timestamp);
@@ -0,0 +1,6 @@
+CREATE TABLE IF NOT EXISTS risk_state (
+    id BIGINT PRIMARY KEY CHECK (id = 1),
+    kill_switch BIGINT NOT NULL,
+    reason TEXT,
+    tripped_at TIMESTAMPTZ
+);
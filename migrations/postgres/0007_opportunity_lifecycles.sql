@@ -0,0 +1,19 @@
+CREATE TABLE IF NOT EXISTS opportunity_lifecycles (
+    id BIGSERIAL PRIMARY KEY,
+    token_pair TEXT NOT NULL,
+    buy_dex TEXT NOT NULL,
+    sell_dex TEXT NOT NULL,
+    first_seen TIMESTAMPTZ NOT NULL,
+    last_seen TIMESTAMPTZ NOT NULL,
+    first_block BIGINT,
+    last_block BIGINT,
+    observations BIGINT NOT NULL,
+    initial_profit_usd TEXT NOT NULL,
+    peak_profit_usd TEXT NOT NULL,
+    last_profit_usd TEXT NOT NULL,
+    close_reason TEXT,
+    closed_at TIMESTAMPTZ
+);
+
+CREATE INDEX IF NOT EXISTS idx_opportunity_lifecycles_route ON opportunity_lifecycles(token_pair, buy_dex, sell_dex);
+CREATE INDEX IF NOT EXISTS idx_opportunity_lifecycles_first_seen ON opportunity_lifecycles(first_seen);
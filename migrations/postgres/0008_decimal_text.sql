@@ -0,0 +1,12 @@
+-- Decimals are stored as TEXT on every backend so both round-trip the exact
+-- rust_decimal value. NUMERIC converts to TEXT without loss.
+ALTER TABLE arbitrage_opportunities
+    ALTER COLUMN buy_price TYPE TEXT USING buy_price::TEXT,
+    ALTER COLUMN sell_price TYPE TEXT USING sell_price::TEXT,
+    ALTER COLUMN price_difference TYPE TEXT USING price_difference::TEXT,
+    ALTER COLUMN profit_usd TYPE TEXT USING profit_usd::TEXT,
+    ALTER COLUMN profit_percentage TYPE TEXT USING profit_percentage::TEXT,
+    ALTER COLUMN trade_amount TYPE TEXT USING trade_amount::TEXT,
+    ALTER COLUMN gas_cost_usd TYPE TEXT USING gas_cost_usd::TEXT;
+
+DROP INDEX IF EXISTS idx_profit_usd;
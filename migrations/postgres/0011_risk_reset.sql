@@ -0,0 +1 @@
+ALTER TABLE risk_state ADD COLUMN reset_at TIMESTAMPTZ;
@@ -0,0 +1,18 @@
+CREATE TABLE IF NOT EXISTS arbitrage_opportunities (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    timestamp DATETIME NOT NULL,
+    token_pair TEXT NOT NULL,
+    buy_dex TEXT NOT NULL,
+    sell_dex TEXT NOT NULL,
+    buy_price DECIMAL(20, 8) NOT NULL,
+    sell_price DECIMAL(20, 8) NOT NULL,
+    price_difference DECIMAL(20, 8) NOT NULL,
+    profit_usd DECIMAL(20, 8) NOT NULL,
+    profit_percentage DECIMAL(10, 4) NOT NULL,
+    trade_amount DECIMAL(20, 8) NOT NULL,
+    gas_cost_usd DECIMAL(20, 8) NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_timestamp ON arbitrage_opportunities(timestamp);
+CREATE INDEX IF NOT EXISTS idx_token_pair ON arbitrage_opportunities(token_pair);
+CREATE INDEX IF NOT EXISTS idx_profit_usd ON arbitrage_opportunities(profit_usd);
@@ -0,0 +1,21 @@
+CREATE TABLE IF NOT EXISTS executions (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    opportunity_id INTEGER NOT NULL REFERENCES arbitrage_opportunities(id),
+    timestamp DATETIME NOT NULL,
+    tx_hashes TEXT NOT NULL,
+    block_number INTEGER,
+    status TEXT NOT NULL,
+    quote_token TEXT NOT NULL,
+    quote_spent TEXT NOT NULL,
+    quote_received TEXT NOT NULL,
+    base_token TEXT NOT NULL,
+    base_residual TEXT NOT NULL,
+    swap_count INTEGER NOT NULL,
+    gas_used INTEGER NOT NULL,
+    gas_cost_native TEXT NOT NULL,
+    gas_cost_usd TEXT NOT NULL,
+    realised_pnl_usd TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_executions_opportunity_id ON executions(opportunity_id);
+CREATE INDEX IF NOT EXISTS idx_executions_timestamp ON executions(timestamp);
@@ -0,0 +1,13 @@
+CREATE TABLE IF NOT EXISTS price_snapshots (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    timestamp DATETIME NOT NULL,
+    dex_name TEXT NOT NULL,
+    token_pair TEXT NOT NULL,
+    block_number INTEGER,
+    price TEXT NOT NULL,
+    liquidity TEXT NOT NULL,
+    latency_ms INTEGER NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_price_snapshots_pair_time ON price_snapshots(token_pair, timestamp);
+CREATE INDEX IF NOT EXISTS idx_price_snapshots_block ON price_snapshots(block_number);
@@ -0,0 +1,19 @@
+CREATE TABLE IF NOT EXISTS evaluated_spreads (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    timestamp DATETIME NOT NULL,
+    token_pair TEXT NOT NULL,
+    buy_dex TEXT NOT NULL,
+    sell_dex TEXT NOT NULL,
+    buy_price TEXT NOT NULL,
+    sell_price TEXT NOT NULL,
+    price_difference TEXT NOT NULL,
+    profit_usd TEXT NOT NULL,
+    profit_percentage TEXT NOT NULL,
+    trade_amount TEXT NOT NULL,
+    gas_cost_usd TEXT NOT NULL,
+    accepted INTEGER NOT NULL,
+    rejection_reason TEXT
+);
+
+CREATE INDEX IF NOT EXISTS idx_evaluated_spreads_pair_time ON evaluated_spreads(token_pair, timestamp);
+CREATE INDEX IF NOT EXISTS idx_evaluated_spreads_reason ON evaluated_spreads(rejection_reason);
@@ -0,0 +1,6 @@
+CREATE TABLE IF NOT EXISTS risk_state (
+    id INTEGER PRIMARY KEY CHECK (id = 1),
+    kill_switch INTEGER NOT NULL,
+    reason TEXT,
+    tripped_at DATETIME
+);
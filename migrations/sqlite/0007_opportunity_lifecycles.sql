@@ -0,0 +1,19 @@
+CREATE TABLE IF NOT EXISTS opportunity_lifecycles (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    token_pair TEXT NOT NULL,
+    buy_dex TEXT NOT NULL,
+    sell_dex TEXT NOT NULL,
+    first_seen DATETIME NOT NULL,
+    last_seen DATETIME NOT NULL,
+    first_block INTEGER,
+    last_block INTEGER,
+    observations INTEGER NOT NULL,
+    initial_profit_usd TEXT NOT NULL,
+    peak_profit_usd TEXT NOT NULL,
+    last_profit_usd TEXT NOT NULL,
+    close_reason TEXT,
+    closed_at DATETIME
+);
+
+CREATE INDEX IF NOT EXISTS idx_opportunity_lifecycles_route ON opportunity_lifecycles(token_pair, buy_dex, sell_dex);
+CREATE INDEX IF NOT EXISTS idx_opportunity_lifecycles_first_seen ON opportunity_lifecycles(first_seen);
@@ -0,0 +1,41 @@
+-- DECIMAL columns have NUMERIC affinity in SQLite, so values were stored as
+-- floats. Convert them to TEXT in place: rebuilding the table would drop the
+-- parent of executions.opportunity_id, which fails the foreign key check at
+-- commit whenever executions exist.
+
+-- profit_usd is no longer numerically ordered, and an indexed column cannot be dropped
+DROP INDEX IF EXISTS idx_profit_usd;
+
+ALTER TABLE arbitrage_opportunities ADD COLUMN buy_price_text TEXT;
+ALTER TABLE arbitrage_opportunities ADD COLUMN sell_price_text TEXT;
+ALTER TABLE arbitrage_opportunities ADD COLUMN price_difference_text TEXT;
+ALTER TABLE arbitrage_opportunities ADD COLUMN profit_usd_text TEXT;
+ALTER TABLE arbitrage_opportunities ADD COLUMN profit_percentage_text TEXT;
+ALTER TABLE arbitrage_opportunities ADD COLUMN trade_amount_text TEXT;
+ALTER TABLE arbitrage_opportunities ADD COLUMN gas_cost_usd_text TEXT;
+
+UPDATE arbitrage_opportunities SET
+    buy_price_text = CAST(buy_price AS TEXT),
+    sell_price_text = CAST(sell_price AS TEXT),
+    price_difference_text = CAST(price_difference AS TEXT),
+    profit_usd_text = CAST(profit_usd AS TEXT),
+    profit_percentage_text = CAST(profit_percentage AS TEXT),
+    trade_amount_text = CAST(trade_amount AS TEXT),
+    gas_cost_usd_text = CAST(gas_cost_usd AS TEXT);
+
+ALTER TABLE arbitrage_opportunities DROP COLUMN buy_price;
+ALTER TABLE arbitrage_opportunities DROP COLUMN sell_price;
+ALTER TABLE arbitrage_opportunities DROP COLUMN price_difference;
+ALTER TABLE arbitrage_opportunities DROP COLUMN profit_usd;
+ALTER TABLE arbitrage_opportunities DROP COLUMN profit_percentage;
+ALTER TABLE arbitrage_opportunities DROP COLUMN trade_amount;
+ALTER TABLE arbitrage_opportunities DROP COLUMN gas_cost_usd;
+
+-- Added columns cannot be NOT NULL without a default; every insert sets them
+ALTER TABLE arbitrage_opportunities RENAME COLUMN buy_price_text TO buy_price;
+ALTER TABLE arbitrage_opportunities RENAME COLUMN sell_price_text TO sell_price;
+ALTER TABLE arbitrage_opportunities RENAME COLUMN price_difference_text TO price_difference;
+ALTER TABLE arbitrage_opportunities RENAME COLUMN profit_usd_text TO profit_usd;
+ALTER TABLE arbitrage_opportunities RENAME COLUMN profit_percentage_text TO profit_percentage;
+ALTER TABLE arbitrage_opportunities RENAME COLUMN trade_amount_text TO trade_amount;
+ALTER TABLE arbitrage_opportunities RENAME COLUMN gas_cost_usd_text TO gas_cost_usd;
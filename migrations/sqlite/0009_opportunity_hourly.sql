@@ -0,0 +1,12 @@
+-- Hourly rollups of opportunities removed by the retention policy, so
+-- long-range trends survive after the raw rows are deleted
+CREATE TABLE IF NOT EXISTS opportunity_hourly (
+    hour DATETIME NOT NULL,
+    token_pair TEXT NOT NULL,
+    buy_dex TEXT NOT NULL,
+    sell_dex TEXT NOT NULL,
+    opportunities INTEGER NOT NULL,
+    total_profit_usd TEXT NOT NULL,
+    max_profit_usd TEXT NOT NULL,
+    PRIMARY KEY (hour, token_pair, buy_dex, sell_dex)
+);
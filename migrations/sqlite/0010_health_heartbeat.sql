@@ -0,0 +1,4 @@
+CREATE TABLE IF NOT EXISTS health_heartbeat (
+    id INTEGER PRIMARY KEY CHECK (id = 1),
+    checked_at DATETIME NOT NULL
+);
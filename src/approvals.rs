@@ -0,0 +1,284 @@
+use crate::config::{ApprovalPolicy, Config, TokenConfig};
+use crate::dex::{get_erc20_abi, to_decimal_units};
+use crate::errors::{ArbitrageError, Result};
+use crate::rpc::{self, RpcProvider};
+use crate::submission::{SubmissionOutcome, TxSubmitter};
+use crate::wallet::load_signer;
+use ethers::{
+    contract::Contract,
+    providers::Middleware,
+    signers::Signer,
+    types::{transaction::eip2718::TypedTransaction, Address, BlockNumber, TransactionRequest, U256},
+    utils::parse_units,
+};
+use std::str::FromStr;
+use std::sync::Arc;
+use tracing::{info, warn};
+
+#[derive(Debug, Clone)]
+pub struct ApprovalStatus {
+    pub token: TokenConfig,
+    pub token_address: Address,
+    pub dex_name: String,
+    pub router: Address,
+    pub balance: U256,
+    pub allowance: U256,
+}
+
+impl ApprovalStatus {
+    // The router must be able to pull everything the wallet holds
+    pub fn is_sufficient(&self) -> bool {
+        self.allowance >= self.balance
+    }
+
+    // What an approval transaction for this router grants under `policy`
+    pub fn approval_amount(&self, policy: ApprovalPolicy) -> U256 {
+        match policy {
+            ApprovalPolicy::Exact => self.balance,
+            ApprovalPolicy::Infinite => U256::MAX,
+        }
+    }
+
+    pub fn describe(&self) -> String {
+        let balance = to_decimal_units(self.balance, self.token.decimals)
+            .map(|d| d.to_string())
+            .unwrap_or_else(|_| self.balance.to_string());
+        let allowance = if self.allowance == U256::MAX {
+            "unlimited".to_string()
+        } else {
+            to_decimal_units(self.allowance, self.token.decimals)
+                .map(|d| d.to_string())
+                .unwrap_or_else(|_| self.allowance.to_string())
+        };
+
+        format!(
+            "{} -> {} ({:?}): balance {}, allowance {}",
+            self.token.symbol, self.dex_name, self.router, balance, allowance
+        )
+    }
+}
+
+pub struct ApprovalManager {
+    provider: Arc<RpcProvider>,
+    config: Config,
+    owner: Address,
+}
+
+impl ApprovalManager {
+    pub fn new(config: &Config) -> Result<Self> {
+        let wallet = config
+            .wallet
+            .as_ref()
+            .ok_or_else(|| ArbitrageError::Config("A [wallet] section is required to manage approvals".to_string()))?;
+        let owner = Address::from_str(&wallet.address)
+            .map_err(|e| ArbitrageError::Config(format!("Invalid wallet address: {}", e)))?;
+        let provider = rpc::connect(&config.polygon)?;
+
+        Ok(Self {
+            provider,
+            config: config.clone(),
+            owner,
+        })
+    }
+
+    pub async fn check(&self) -> Result<Vec<ApprovalStatus>> {
+        let mut statuses = Vec::new();
+
+        for token in self.config.tokens.all() {
+            let token_address = Address::from_str(&token.address)
+                .map_err(|e| ArbitrageError::Config(format!("Invalid {} address: {}", token.symbol, e)))?;
+            let contract = Contract::new(token_address, get_erc20_abi(), self.provider.clone());
+
+            let balance: U256 = contract
+                .method::<_, U256>("balanceOf", self.owner)
+                .map_err(|e| ArbitrageError::PriceFetch(format!("balanceOf encoding error: {}", e)))?
+                .call()
+                .await?;
+
+            for dex in self.config.dexes.all().into_iter().filter(|dex| dex.enabled) {
+                let router = Address::from_str(&dex.router_address)
+                    .map_err(|e| ArbitrageError::Config(format!("Invalid router address for {}: {}", dex.name, e)))?;
+                let allowance: U256 = contract
+                    .method::<_, U256>("allowance", (self.owner, router))
+                    .map_err(|e| ArbitrageError::PriceFetch(format!("allowance encoding error: {}", e)))?
+                    .call()
+                    .await?;
+
+                statuses.push(ApprovalStatus {
+                    token: token.clone(),
+                    token_address,
+                    dex_name: dex.name.clone(),
+                    router,
+                    balance,
+                    allowance,
+                });
+            }
+        }
+
+        Ok(statuses)
+    }
+
+    pub fn report(statuses: &[ApprovalStatus]) {
+        for status in statuses {
+            if status.is_sufficient() {
+                info!("Approval OK: {}", status.describe());
+            } else if status.allowance.is_zero() {
+                warn!("Approval missing: {}", status.describe());
+            } else {
+                warn!("Approval insufficient: {}", status.describe());
+            }
+        }
+    }
+
+    // Signs one approval per insufficient allowance with consecutive nonces and
+    // submits them together, so a bundle relay includes all of them or none
+    pub async fn approve_missing(&self, statuses: &[ApprovalStatus]) -> Result<Option<SubmissionOutcome>> {
+        let missing: Vec<&ApprovalStatus> = statuses.iter().filter(|status| !status.is_sufficient()).collect();
+        if missing.is_empty() {
+            return Ok(None);
+        }
+
+        let wallet_config = self.config.wallet.as_ref().expect("checked in ApprovalManager::new");
+        let signer = load_signer(wallet_config, self.config.polygon.chain_id)?;
+        let submitter = TxSubmitter::new(&self.config)?;
+
+        let gas_price: U256 = parse_units(self.config.polygon.gas_price_gwei, "gwei")
+            .map_err(|e| ArbitrageError::Config(format!("Invalid gas price: {}", e)))?
+            .into();
+        let mut nonce = self
+            .provider
+            .get_transaction_count(self.owner, Some(BlockNumber::Pending.into()))
+            .await?;
+
+        let mut signed_txs = Vec::with_capacity(missing.len());
+        for status in missing {
+            let amount = status.approval_amount(self.config.approvals.policy);
+
+            let contract = Contract::new(status.token_address, get_erc20_abi(), self.provider.clone());
+            let data = contract
+                .encode("approve", (status.router, amount))
+                .map_err(|e| ArbitrageError::Submission(format!("approve encoding error: {}", e)))?;
+
+            let tx: TypedTransaction = TransactionRequest::new()
+                .from(self.owner)
+                .to(status.token_address)
+                .data(data)
+                .nonce(nonce)
+                .gas(self.config.approvals.gas_limit)
+                .gas_price(gas_price)
+                .chain_id(self.config.polygon.chain_id)
+                .into();
+            let signature = signer
+                .sign_transaction(&tx)
+                .await
+                .map_err(|e| ArbitrageError::Submission(format!("Failed to sign approval: {}", e)))?;
+
+            info!("Approving {} for {} ({:?} policy)", status.token.symbol, status.dex_name, self.config.approvals.policy);
+            signed_txs.push(tx.rlp_signed(&signature));
+            nonce += U256::one();
+        }
+
+        Ok(Some(submitter.submit(&signed_txs).await?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::tests::test_config;
+    use crate::config::{SubmissionBackend, WalletConfig};
+    use crate::submission::tests::mock_relay;
+    use ethers::utils::rlp::Rlp;
+    use serde_json::{json, Value};
+
+    fn status(balance: u64, allowance: U256) -> ApprovalStatus {
+        let config = test_config();
+        ApprovalStatus {
+            token_address: Address::from_str(&config.tokens.usdc.address).unwrap(),
+            token: config.tokens.usdc,
+            dex_name: config.dexes.quickswap.name,
+            router: Address::from_str(&config.dexes.quickswap.router_address).unwrap(),
+            balance: U256::from(balance),
+            allowance,
+        }
+    }
+
+    #[test]
+    fn allowances_must_cover_the_whole_balance() {
+        assert!(status(1_000_000, U256::from(1_000_000)).is_sufficient());
+        assert!(status(1_000_000, U256::MAX).is_sufficient());
+        assert!(status(0, U256::zero()).is_sufficient());
+        assert!(!status(1_000_000, U256::from(999_999)).is_sufficient());
+        assert!(!status(1_000_000, U256::zero()).is_sufficient());
+    }
+
+    #[test]
+    fn policies_approve_the_balance_or_everything() {
+        let partial = status(2_500_000, U256::from(1_000_000));
+
+        assert_eq!(partial.approval_amount(ApprovalPolicy::Exact), U256::from(2_500_000));
+        assert_eq!(partial.approval_amount(ApprovalPolicy::Infinite), U256::MAX);
+        assert!(status(2_500_000, partial.approval_amount(ApprovalPolicy::Exact)).is_sufficient());
+    }
+
+    #[test]
+    fn descriptions_use_token_units() {
+        assert_eq!(
+            status(2_500_000, U256::MAX).describe(),
+            format!("USDC -> QuickSwap ({:?}): balance 2.500000, allowance unlimited", status(0, U256::zero()).router)
+        );
+    }
+
+    #[tokio::test]
+    async fn missing_approvals_are_submitted_as_one_bundle() {
+        let (url, requests) = mock_relay(|request| {
+            let result = match request["method"].as_str().unwrap() {
+                "eth_getTransactionCount" => json!("0x7"),
+                "eth_blockNumber" => json!("0x2faf080"),
+                "eth_sendBundle" => json!({ "bundleHash": "0xb0b0" }),
+                method => panic!("unexpected {}", method),
+            };
+            json!({ "jsonrpc": "2.0", "id": request["id"], "result": result })
+        });
+
+        let mut config = test_config();
+        config.polygon.rpc_url = url.clone();
+        config.submission.backend = SubmissionBackend::Bundle;
+        config.submission.relay_url = Some(url);
+        // The well-known first development account
+        config.wallet = Some(WalletConfig {
+            address: "0xf39Fd6e51aad88F6F4ce6aB8827279cffFb92266".to_string(),
+            private_key: Some("0xac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80".to_string()),
+            min_native_balance: 1.0,
+            snapshot_interval: 60,
+        });
+        let manager = ApprovalManager::new(&config).unwrap();
+
+        assert!(manager.approve_missing(&[status(1_000_000, U256::MAX)]).await.unwrap().is_none());
+        assert!(requests.lock().unwrap().is_empty());
+
+        let statuses = [
+            status(1_000_000, U256::zero()),
+            status(1_000_000, U256::MAX),
+            status(2_000_000, U256::from(5)),
+        ];
+        let outcome = manager.approve_missing(&statuses).await.unwrap().unwrap();
+        assert_eq!(outcome.tx_hashes.len(), 2);
+        assert_eq!(outcome.target_block, Some(50_000_002));
+
+        let requests = requests.lock().unwrap();
+        let bundles: Vec<&Value> = requests.iter().filter(|request| request["method"] == "eth_sendBundle").collect();
+        assert_eq!(bundles.len(), 1);
+        let nonces: Vec<U256> = bundles[0]["params"][0]["txs"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|tx| {
+                let raw = hex::decode(tx.as_str().unwrap().trim_start_matches("0x")).unwrap();
+                let (tx, _) = TypedTransaction::decode_signed(&Rlp::new(&raw)).unwrap();
+                *tx.nonce().unwrap()
+            })
+            .collect();
+        assert_eq!(nonces, vec![U256::from(7), U256::from(8)]);
+    }
+}
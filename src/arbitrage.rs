@@ -2,14 +2,32 @@ use crate::config::Config;
 use crate::database::{Database, ArbitrageOpportunity};
 use crate::dex::{DexManager, TokenPair, PriceQuote};
 use crate::errors::{ArbitrageError, Result};
-use chrono::Utc;
+use crate::lifecycle::{LifecycleTracker, PairObservation};
+use crate::health::HEALTH;
+use crate::metrics::METRICS;
+use crate::risk::RiskManager;
+use crate::rpc;
+use crate::wallet::{cap_trade_amount, Wallet, WalletBalances};
+use chrono::{DateTime, Utc};
+use rust_decimal::prelude::ToPrimitive;
 use rust_decimal::Decimal;
-use tracing::{info, warn, error};
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+use tracing::{info, warn};
 
 pub struct ArbitrageDetector {
     config: Config,
     database: Database,
     dex_manager: DexManager,
+    risk: RiskManager,
+    wallet: Option<Wallet>,
+    balances: Option<WalletBalances>,
+    last_balance_snapshot: Option<Instant>,
+    cycle: u64,
+    last_price_prune: Option<Instant>,
+    rejected_spreads: AtomicU64,
+    lifecycles: LifecycleTracker,
 }
 
 impl ArbitrageDetector {
@@ -17,128 +35,627 @@ impl ArbitrageDetector {
         let dex_manager = DexManager::new(config.clone()).await
             .map_err(|e| ArbitrageError::Config(format!("Failed to initialize DEX manager: {}", e)))?;
         
+        let wallet = match config.wallet.clone() {
+            Some(wallet_config) => {
+                let provider = rpc::connect(&config.polygon)?;
+                Some(Wallet::new(provider, &config, wallet_config)?)
+            }
+            None => None,
+        };
+        
+        let lifecycles = LifecycleTracker::new(&database).await;
+        
         Ok(Self {
+            risk: RiskManager::new(config.risk.clone()),
             config,
             database,
             dex_manager,
+            wallet,
+            balances: None,
+            last_balance_snapshot: None,
+            cycle: 0,
+            last_price_prune: None,
+            rejected_spreads: AtomicU64::new(0),
+            lifecycles,
         })
     }
     
+    // Swaps in a reloaded configuration. The new fetchers and wallet are built
+    // before anything is replaced, so a failure leaves the detector as it was.
+    pub async fn apply_config(&mut self, config: Config, rebuild_dexes: bool) -> Result<()> {
+        if rebuild_dexes {
+            let dex_manager = DexManager::new(config.clone()).await
+                .map_err(|e| ArbitrageError::Config(format!("Failed to rebuild DEX manager: {}", e)))?;
+            // The wallet tracks balances and allowances for the configured tokens and routers
+            let wallet = self.wallet.as_ref().map(|wallet| wallet.reconfigured(&config)).transpose()?;
+            self.dex_manager = dex_manager;
+            self.wallet = wallet;
+        }
+        
+        self.risk = RiskManager::new(config.risk.clone());
+        self.config = config;
+        Ok(())
+    }
+    
     pub async fn check_arbitrage_opportunities(&mut self) -> Result<Vec<ArbitrageOpportunity>> {
+        let started = Instant::now();
+        let result = self.run_cycle().await;
+        METRICS.cycles.inc(&[if result.is_ok() { "ok" } else { "error" }]);
+        HEALTH.record_cycle(result.as_ref().err().map(|e| e.to_string()));
+        METRICS.cycle_duration.observe_duration(&[], started.elapsed());
+        result
+    }
+    
+    async fn run_cycle(&mut self) -> Result<Vec<ArbitrageOpportunity>> {
         let mut opportunities = Vec::new();
         
-        // Check WETH/USDC pair
-        let weth_usdc_pair = TokenPair {
-            token0: self.config.tokens.weth.clone(),
-            token1: self.config.tokens.usdc.clone(),
-        };
+        self.refresh_balances().await;
+        
+        self.cycle += 1;
+        let history = &self.config.database.price_history;
+        let record_prices = history.enabled && self.cycle.is_multiple_of(history.sample_every.max(1) as u64);
         
-        if let Some(opportunity) = self.check_token_pair(&weth_usdc_pair).await? {
-            opportunities.push(opportunity);
+        for token_pair in token_pairs(&self.config) {
+            let pair_name = format!("{}/{}", token_pair.token0.symbol, token_pair.token1.symbol);
+            let observation = self.check_token_pair(&token_pair, record_prices).await?;
+            self.lifecycles.observe(&self.database, &pair_name, &observation).await;
+            
+            if let PairObservation::Opportunity { opportunity, .. } = observation {
+                opportunities.push(*opportunity);
+            }
         }
         
-        // Check WBTC/USDC pair
-        let wbtc_usdc_pair = TokenPair {
-            token0: self.config.tokens.wbtc.clone(),
-            token1: self.config.tokens.usdc.clone(),
-        };
+        self.prune_price_history().await;
         
-        if let Some(opportunity) = self.check_token_pair(&wbtc_usdc_pair).await? {
-            opportunities.push(opportunity);
+        Ok(opportunities)
+    }
+    
+    async fn prune_price_history(&mut self) {
+        let retention_days = self.config.database.price_history.retention_days;
+        if retention_days == 0 || self.last_price_prune.is_some_and(|last| last.elapsed() < Duration::from_secs(3600)) {
+            return;
         }
         
-        Ok(opportunities)
+        let cutoff = Utc::now() - chrono::Duration::days(retention_days as i64);
+        match self.database.prune_price_snapshots(cutoff).await {
+            Ok(0) => {}
+            Ok(deleted) => info!("Pruned {} price snapshots older than {} days", deleted, retention_days),
+            Err(e) => warn!("Failed to prune price snapshots: {}", e),
+        }
+        self.last_price_prune = Some(Instant::now());
+    }
+    
+    async fn refresh_balances(&mut self) {
+        let Some(wallet) = &self.wallet else {
+            return;
+        };
+        
+        match wallet.fetch_balances().await {
+            Ok(balances) => {
+                let snapshot_due = self.last_balance_snapshot.is_none_or(|last| {
+                    last.elapsed() >= Duration::from_secs(wallet.config().snapshot_interval)
+                });
+                
+                if snapshot_due {
+                    match self.database.save_balance_snapshot(&balances).await {
+                        Ok(()) => self.last_balance_snapshot = Some(Instant::now()),
+                        Err(e) => warn!("Failed to save balance snapshot: {}", e),
+                    }
+                }
+                
+                self.balances = Some(balances);
+            }
+            Err(e) => {
+                // Keep the previous balances so sizing still has something to go on
+                warn!("Failed to refresh wallet balances: {}", e);
+            }
+        }
     }
     
-    async fn check_token_pair(&self, token_pair: &TokenPair) -> Result<Option<ArbitrageOpportunity>> {
+    async fn check_token_pair(&self, token_pair: &TokenPair, record_prices: bool) -> Result<PairObservation> {
         let prices = self.dex_manager.get_all_prices(token_pair).await;
         
+        if record_prices && !prices.is_empty() {
+            if let Err(e) = self.database.save_price_snapshots(&prices).await {
+                warn!("Failed to save price snapshots: {}", e);
+            }
+        }
+        
+        if let Some(block) = prices.iter().filter_map(|quote| quote.block_number).max() {
+            METRICS.last_block.set(&[], block as f64);
+        }
+        
         if prices.len() < 2 {
             warn!("Not enough price quotes for {}/{}", token_pair.token0.symbol, token_pair.token1.symbol);
-            return Ok(None);
+            return Ok(PairObservation::NoQuotes);
         }
         
-        // Find best buy and sell prices
-        let mut best_buy = &prices[0];
-        let mut best_sell = &prices[0];
+        let Some(evaluation) = evaluate_quotes(&self.config, self.balances.as_ref(), &prices, token_pair, Utc::now()) else {
+            return Ok(PairObservation::NoQuotes);
+        };
+        
+        self.record_spread(&evaluation).await;
         
-        for price in &prices {
-            if price.price < best_buy.price {
-                best_buy = price;
-            }
-            if price.price > best_sell.price {
-                best_sell = price;
+        let spread = &evaluation.spread;
+        let outcome = evaluation.rejection.map_or("accepted", |reason| reason.as_str());
+        METRICS.spreads.inc(&[&spread.token_pair, outcome]);
+        if !spread.buy_price.is_zero() {
+            let percent = (spread.sell_price - spread.buy_price) / spread.buy_price * Decimal::ONE_HUNDRED;
+            METRICS.spread_percent.observe(&[&spread.token_pair], percent.to_f64().unwrap_or_default());
+        }
+        
+        if let Some(reason) = evaluation.rejection {
+            return Ok(PairObservation::Rejected(reason));
+        }
+        
+        let mut opportunity = evaluation.spread;
+        METRICS.opportunities.inc(&[&opportunity.token_pair]);
+        METRICS.estimated_profit.inc_by(&[&opportunity.token_pair], opportunity.profit_usd.to_f64().unwrap_or_default());
+        info!(
+            token_pair = %opportunity.token_pair,
+            buy_dex = %opportunity.buy_dex,
+            sell_dex = %opportunity.sell_dex,
+            buy_price = %opportunity.buy_price,
+            sell_price = %opportunity.sell_price,
+            profit_usd = %opportunity.profit_usd,
+            profit_percentage = %opportunity.profit_percentage,
+            trade_amount = %opportunity.trade_amount,
+            gas_cost_usd = %opportunity.gas_cost_usd,
+            "Arbitrage opportunity found"
+        );
+        
+        // Save to database
+        let id = self.database.save_opportunity(&opportunity).await
+            .map_err(|e| ArbitrageError::Database(e.to_string()))?;
+        opportunity.id = Some(id);
+        
+        // Detection carries on regardless; the risk gate only decides whether this may be executed
+        match self.risk.pre_trade_check(&self.database, &opportunity).await {
+            Ok(Some(violation)) => warn!("Execution blocked for {}: {}", opportunity.token_pair, violation),
+            Ok(None) => {}
+            Err(e) => warn!("Risk check failed: {}", e),
+        }
+        
+        let block = prices.iter().filter_map(|quote| quote.block_number).max();
+        Ok(PairObservation::Opportunity { opportunity: Box::new(opportunity), block })
+    }
+    
+    async fn record_spread(&self, evaluation: &SpreadEvaluation) {
+        let spread_log = &self.config.arbitrage.spread_log;
+        if !spread_log.enabled {
+            return;
+        }
+        
+        // Accepted spreads are always kept; rejections are sampled
+        if evaluation.rejection.is_some() {
+            let seen = self.rejected_spreads.fetch_add(1, Ordering::Relaxed);
+            if !seen.is_multiple_of(spread_log.sample_every.max(1) as u64) {
+                return;
             }
         }
         
-        // Skip if buying and selling on the same DEX
-        if best_buy.dex_name == best_sell.dex_name {
-            return Ok(None);
+        if let Err(e) = self.database.save_evaluated_spread(&evaluation.spread, evaluation.rejection).await {
+            warn!("Failed to save evaluated spread: {}", e);
+        }
+    }
+}
+
+pub fn token_pairs(config: &Config) -> Vec<TokenPair> {
+    vec![
+        TokenPair {
+            token0: config.tokens.weth.clone(),
+            token1: config.tokens.usdc.clone(),
+        },
+        TokenPair {
+            token0: config.tokens.wbtc.clone(),
+            token1: config.tokens.usdc.clone(),
+        },
+    ]
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RejectionReason {
+    BelowUsdThreshold,
+    BelowPercentageThreshold,
+    SameDex,
+    InsufficientLiquidity,
+    StaleQuote,
+    SimulationFailed,
+}
+
+impl RejectionReason {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::BelowUsdThreshold => "below_usd_threshold",
+            Self::BelowPercentageThreshold => "below_percentage_threshold",
+            Self::SameDex => "same_dex",
+            Self::InsufficientLiquidity => "insufficient_liquidity",
+            Self::StaleQuote => "stale_quote",
+            Self::SimulationFailed => "simulation_failed",
+        }
+    }
+}
+
+// The best spread seen for a pair, with the reason it was dropped if it
+// did not make it through to an opportunity
+#[derive(Debug, Clone)]
+pub struct SpreadEvaluation {
+    pub spread: ArbitrageOpportunity,
+    pub rejection: Option<RejectionReason>,
+}
+
+// Pure detection logic shared by the live loop and backtests: pick the best
+// buy and sell quotes and check the spread against the thresholds. `now` is
+// the evaluation time, used to judge quote staleness.
+pub fn evaluate_quotes(
+    config: &Config,
+    balances: Option<&WalletBalances>,
+    prices: &[PriceQuote],
+    token_pair: &TokenPair,
+    now: DateTime<Utc>,
+) -> Option<SpreadEvaluation> {
+    if prices.len() < 2 {
+        return None;
+    }
+    
+    // Find best buy and sell prices
+    let mut best_buy = &prices[0];
+    let mut best_sell = &prices[0];
+    
+    for price in prices {
+        if price.price < best_buy.price {
+            best_buy = price;
+        }
+        if price.price > best_sell.price {
+            best_sell = price;
+        }
+    }
+    
+    let reject = |spread, reason| Some(SpreadEvaluation { spread, rejection: Some(reason) });
+    
+    let spread = match calculate_arbitrage_profit(config, balances, best_buy, best_sell, token_pair) {
+        Ok(spread) => spread,
+        Err(e) => {
+            warn!("Profit simulation failed for {}: {}", best_buy.token_pair, e);
+            return reject(unpriced_spread(best_buy, best_sell, token_pair), RejectionReason::SimulationFailed);
         }
+    };
+    
+    // Skip if buying and selling on the same DEX
+    if best_buy.dex_name == best_sell.dex_name {
+        return reject(spread, RejectionReason::SameDex);
+    }
+    
+    let max_age = chrono::Duration::seconds(config.arbitrage.max_quote_age_secs as i64);
+    if config.arbitrage.max_quote_age_secs > 0 &&
+       (now - best_buy.timestamp > max_age || now - best_sell.timestamp > max_age) {
+        return reject(spread, RejectionReason::StaleQuote);
+    }
+    
+    // Chain-backed fetchers report zero liquidity, meaning unknown; only legs
+    // with a reported liquidity can fail the check
+    let min_liquidity = Decimal::try_from(config.arbitrage.min_liquidity).unwrap_or(Decimal::ZERO);
+    let too_shallow = |quote: &PriceQuote| !quote.liquidity.is_zero() && quote.liquidity < min_liquidity;
+    if too_shallow(best_buy) || too_shallow(best_sell) {
+        return reject(spread, RejectionReason::InsufficientLiquidity);
+    }
+    
+    // Check if opportunity meets minimum requirements
+    if spread.profit_usd < Decimal::try_from(config.arbitrage.min_profit_usd).unwrap() {
+        return reject(spread, RejectionReason::BelowUsdThreshold);
+    }
+    if spread.profit_percentage < Decimal::try_from(config.arbitrage.min_profit_percentage).unwrap() {
+        return reject(spread, RejectionReason::BelowPercentageThreshold);
+    }
+    
+    Some(SpreadEvaluation { spread, rejection: None })
+}
+
+fn unpriced_spread(buy_quote: &PriceQuote, sell_quote: &PriceQuote, token_pair: &TokenPair) -> ArbitrageOpportunity {
+    ArbitrageOpportunity {
+        id: None,
+        timestamp: Utc::now(),
+        token_pair: format!("{}/{}", token_pair.token0.symbol, token_pair.token1.symbol),
+        buy_dex: buy_quote.dex_name.clone(),
+        sell_dex: sell_quote.dex_name.clone(),
+        buy_price: buy_quote.price,
+        sell_price: sell_quote.price,
+        price_difference: sell_quote.price - buy_quote.price,
+        profit_usd: Decimal::ZERO,
+        profit_percentage: Decimal::ZERO,
+        trade_amount: Decimal::ZERO,
+        gas_cost_usd: Decimal::ZERO,
+    }
+}
+
+fn calculate_arbitrage_profit(
+    config: &Config,
+    balances: Option<&WalletBalances>,
+    buy_quote: &PriceQuote,
+    sell_quote: &PriceQuote,
+    token_pair: &TokenPair,
+) -> Result<ArbitrageOpportunity> {
+    let trade_amount_usd = Decimal::try_from(config.arbitrage.trade_amount_usd)
+        .map_err(|e| ArbitrageError::Calculation(format!("Invalid trade amount: {}", e)))?;
+    
+    // Never size a trade beyond what the wallet holds of the quote token
+    let trade_amount_usd = match balances {
+        Some(balances) => cap_trade_amount(trade_amount_usd, balances, &token_pair.token1.symbol, &buy_quote.dex_name),
+        None => trade_amount_usd,
+    };
+    
+    if buy_quote.price <= Decimal::ZERO || sell_quote.price <= Decimal::ZERO {
+        return Err(ArbitrageError::Calculation(format!(
+            "Non-positive price quoted ({} on {}, {} on {})",
+            buy_quote.price, buy_quote.dex_name, sell_quote.price, sell_quote.dex_name
+        )));
+    }
+    
+    // Calculate trade amount in base token
+    let trade_amount_tokens = trade_amount_usd / buy_quote.price;
+    
+    // Calculate costs and revenues
+    let buy_cost = trade_amount_tokens * buy_quote.price;
+    let sell_revenue = trade_amount_tokens * sell_quote.price;
+    
+    // Calculate gas cost (simplified)
+    let gas_cost_usd = Decimal::try_from(
+        config.polygon.gas_price_gwei * (config.polygon.gas_limit as f64) * 1e-9 * 2.0 // Assume 2 transactions
+            * config.polygon.native_price_usd
+    ).map_err(|e| ArbitrageError::Calculation(format!("Gas cost calculation error: {}", e)))?;
+    
+    // Calculate profit
+    let gross_profit = sell_revenue - buy_cost;
+    let net_profit = gross_profit - gas_cost_usd;
+    let profit_percentage = if buy_cost > Decimal::ZERO {
+        (net_profit / buy_cost) * Decimal::from(100)
+    } else {
+        Decimal::ZERO
+    };
+    
+    let price_difference = sell_quote.price - buy_quote.price;
+    
+    Ok(ArbitrageOpportunity {
+        id: None,
+        timestamp: Utc::now(),
+        token_pair: format!("{}/{}", token_pair.token0.symbol, token_pair.token1.symbol),
+        buy_dex: buy_quote.dex_name.clone(),
+        sell_dex: sell_quote.dex_name.clone(),
+        buy_price: buy_quote.price,
+        sell_price: sell_quote.price,
+        price_difference,
+        profit_usd: net_profit,
+        profit_percentage,
+        trade_amount: trade_amount_usd,
+        gas_cost_usd,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::tests::test_config;
+    use crate::config::{PriceScript, SyntheticDexConfig};
+    use crate::database::tests::evaluated_spread_rows;
+    use std::collections::HashMap;
+    
+    fn synthetic_dex(name: &str, weth_usdc: f64, wbtc_usdc: f64) -> SyntheticDexConfig {
+        SyntheticDexConfig {
+            name: name.to_string(),
+            enabled: true,
+            liquidity: 1_000_000.0,
+            pairs: HashMap::from([
+                ("WETH/USDC".to_string(), PriceScript::Static { price: weth_usdc }),
+                ("WBTC/USDC".to_string(), PriceScript::Static { price: wbtc_usdc }),
+            ]),
+        }
+    }
+    
+    fn quote(dex_name: &str, price: &str) -> PriceQuote {
+        PriceQuote {
+            dex_name: dex_name.to_string(),
+            token_pair: "WETH/USDC".to_string(),
+            price: price.parse().unwrap(),
+            liquidity: Decimal::from(1_000_000),
+            timestamp: Utc::now(),
+            block_number: Some(1),
+            latency_ms: 10,
+        }
+    }
+    
+    fn evaluate(config: &Config, prices: &[PriceQuote]) -> Option<SpreadEvaluation> {
+        evaluate_quotes(config, None, prices, &token_pairs(config)[0], Utc::now())
+    }
+    
+    fn rejection(config: &Config, prices: &[PriceQuote]) -> Option<RejectionReason> {
+        evaluate(config, prices).unwrap().rejection
+    }
+    
+    #[test]
+    fn fewer_than_two_quotes_are_not_evaluated() {
+        assert!(evaluate(&test_config(), &[quote("QuickSwap", "1800")]).is_none());
+    }
+    
+    #[test]
+    fn zero_prices_fail_the_simulation() {
+        let config = test_config();
         
-        let opportunity = self.calculate_arbitrage_profit(best_buy, best_sell, token_pair)?;
+        let evaluation = evaluate(&config, &[quote("QuickSwap", "0"), quote("SushiSwap", "1800")]).unwrap();
+        assert_eq!(evaluation.rejection, Some(RejectionReason::SimulationFailed));
+        assert_eq!(evaluation.spread.buy_price, Decimal::ZERO);
+    }
+    
+    #[test]
+    fn same_dex_spreads_are_rejected() {
+        let config = test_config();
+        assert_eq!(rejection(&config, &[quote("QuickSwap", "1800"), quote("QuickSwap", "1818")]), Some(RejectionReason::SameDex));
+    }
+    
+    #[test]
+    fn stale_quotes_are_rejected() {
+        let mut config = test_config();
+        config.arbitrage.max_quote_age_secs = 30;
+        let stale = PriceQuote { timestamp: Utc::now() - chrono::Duration::seconds(31), ..quote("SushiSwap", "1818") };
         
-        // Check if opportunity meets minimum requirements
-        if opportunity.profit_usd >= Decimal::try_from(self.config.arbitrage.min_profit_usd).unwrap() &&
-           opportunity.profit_percentage >= Decimal::try_from(self.config.arbitrage.min_profit_percentage).unwrap() {
-            
-            info!("Arbitrage opportunity found: {}", serde_json::to_string(&opportunity).unwrap_or_default());
-            
-            // Save to database
-            self.database.save_opportunity(&opportunity).await
-                .map_err(|e| ArbitrageError::Database(e))?;
-            
-            return Ok(Some(opportunity));
+        assert_eq!(rejection(&config, &[quote("QuickSwap", "1800"), stale.clone()]), Some(RejectionReason::StaleQuote));
+        config.arbitrage.max_quote_age_secs = 0;
+        assert_eq!(rejection(&config, &[quote("QuickSwap", "1800"), stale]), None);
+    }
+    
+    #[test]
+    fn unknown_liquidity_skips_the_liquidity_check() {
+        let mut config = test_config();
+        config.arbitrage.min_liquidity = 10_000.0;
+        let unknown = PriceQuote { liquidity: Decimal::ZERO, ..quote("QuickSwap", "1800") };
+        let shallow = PriceQuote { liquidity: Decimal::from(9_999), ..quote("QuickSwap", "1800") };
+        
+        assert_eq!(rejection(&config, &[unknown, quote("SushiSwap", "1818")]), None);
+        assert_eq!(rejection(&config, &[shallow, quote("SushiSwap", "1818")]), Some(RejectionReason::InsufficientLiquidity));
+    }
+    
+    #[test]
+    fn spreads_below_the_usd_threshold_are_rejected() {
+        // $1000 at 1800 gains about $0.56 before gas
+        let config = test_config();
+        assert_eq!(rejection(&config, &[quote("QuickSwap", "1800"), quote("SushiSwap", "1801")]), Some(RejectionReason::BelowUsdThreshold));
+    }
+    
+    #[test]
+    fn spreads_below_the_percentage_threshold_are_rejected() {
+        let mut config = test_config();
+        config.arbitrage.min_profit_usd = 0.5;
+        let prices = [quote("QuickSwap", "1800"), quote("SushiSwap", "1801.5")];
+        
+        assert_eq!(rejection(&config, &prices), Some(RejectionReason::BelowPercentageThreshold));
+        config.arbitrage.min_profit_percentage = 0.05;
+        assert_eq!(rejection(&config, &prices), None);
+    }
+    
+    #[test]
+    fn rejection_reasons_are_checked_in_order() {
+        let mut config = test_config();
+        config.arbitrage.max_quote_age_secs = 30;
+        config.arbitrage.min_liquidity = 10_000.0;
+        let stale = Utc::now() - chrono::Duration::minutes(5);
+        let shallow = Decimal::from(100);
+        let mut buy = PriceQuote { price: Decimal::ZERO, timestamp: stale, liquidity: shallow, ..quote("QuickSwap", "1") };
+        let mut sell = PriceQuote { timestamp: stale, liquidity: shallow, ..quote("QuickSwap", "1801.5") };
+        
+        // Fix one failing check at a time; the next one down the list reports
+        assert_eq!(rejection(&config, &[buy.clone(), sell.clone()]), Some(RejectionReason::SimulationFailed));
+        buy.price = Decimal::from(1800);
+        assert_eq!(rejection(&config, &[buy.clone(), sell.clone()]), Some(RejectionReason::SameDex));
+        sell.dex_name = "SushiSwap".to_string();
+        assert_eq!(rejection(&config, &[buy.clone(), sell.clone()]), Some(RejectionReason::StaleQuote));
+        (buy.timestamp, sell.timestamp) = (Utc::now(), Utc::now());
+        assert_eq!(rejection(&config, &[buy.clone(), sell.clone()]), Some(RejectionReason::InsufficientLiquidity));
+        (buy.liquidity, sell.liquidity) = (Decimal::from(1_000_000), Decimal::from(1_000_000));
+        assert_eq!(rejection(&config, &[buy.clone(), sell.clone()]), Some(RejectionReason::BelowUsdThreshold));
+        config.arbitrage.min_profit_usd = 0.5;
+        assert_eq!(rejection(&config, &[buy.clone(), sell.clone()]), Some(RejectionReason::BelowPercentageThreshold));
+        config.arbitrage.min_profit_percentage = 0.05;
+        assert_eq!(rejection(&config, &[buy, sell]), None);
+    }
+    
+    #[tokio::test]
+    async fn rejected_spreads_are_sampled_and_accepted_ones_kept() {
+        let mut config = test_config();
+        config.dexes.uniswap_v2.enabled = false;
+        config.dexes.quickswap.enabled = false;
+        config.dexes.sushiswap.enabled = false;
+        // WETH/USDC is accepted every cycle, WBTC/USDC always rejected
+        config.synthetic_dexes = vec![
+            synthetic_dex("Synthetic A", 1800.0, 34_000.0),
+            synthetic_dex("Synthetic B", 1818.0, 34_005.0),
+        ];
+        config.arbitrage.spread_log.sample_every = 3;
+        
+        let database = Database::new("sqlite::memory:").await.unwrap();
+        database.migrate().await.unwrap();
+        let mut detector = ArbitrageDetector::new(config, database.clone()).await.unwrap();
+        for _ in 0..4 {
+            detector.check_arbitrage_opportunities().await.unwrap();
         }
         
-        Ok(None)
+        // The 1st and 4th of four rejections are stored
+        let rows = evaluated_spread_rows(&database).await;
+        assert_eq!(rows.iter().filter(|(accepted, _)| *accepted).count(), 4);
+        assert_eq!(
+            rows.iter().filter_map(|(_, reason)| reason.as_deref()).collect::<Vec<_>>(),
+            vec!["below_usd_threshold", "below_usd_threshold"]
+        );
     }
     
-    fn calculate_arbitrage_profit(
-        &self,
-        buy_quote: &PriceQuote,
-        sell_quote: &PriceQuote,
-        token_pair: &TokenPair,
-    ) -> Result<ArbitrageOpportunity> {
-        let trade_amount_usd = Decimal::try_from(self.config.arbitrage.trade_amount_usd)
-            .map_err(|e| ArbitrageError::Calculation(format!("Invalid trade amount: {}", e)))?;
+    #[tokio::test]
+    async fn synthetic_spreads_are_stored_only_above_the_threshold() {
+        let mut config = test_config();
+        config.dexes.uniswap_v2.enabled = false;
+        config.dexes.quickswap.enabled = false;
+        config.dexes.sushiswap.enabled = false;
+        // WETH/USDC is 1% apart, WBTC/USDC under the 0.1% minimum
+        config.synthetic_dexes = vec![
+            synthetic_dex("Synthetic A", 1800.0, 34_000.0),
+            synthetic_dex("Synthetic B", 1818.0, 34_005.0),
+        ];
         
-        // Calculate trade amount in base token
-        let trade_amount_tokens = trade_amount_usd / buy_quote.price;
+        let database = Database::new("sqlite::memory:").await.unwrap();
+        database.migrate().await.unwrap();
+        let mut detector = ArbitrageDetector::new(config.clone(), database.clone()).await.unwrap();
         
-        // Calculate costs and revenues
-        let buy_cost = trade_amount_tokens * buy_quote.price;
-        let sell_revenue = trade_amount_tokens * sell_quote.price;
+        let found = detector.check_arbitrage_opportunities().await.unwrap();
+        let stored = database.get_recent_opportunities(10).await.unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!(stored.len(), 1);
         
-        // Calculate gas cost (simplified)
-        let gas_cost_usd = Decimal::try_from(
-            self.config.polygon.gas_price_gwei * (self.config.polygon.gas_limit as f64) * 1e-9 * 2.0 // Assume 2 transactions
-        ).map_err(|e| ArbitrageError::Calculation(format!("Gas cost calculation error: {}", e)))?;
+        let opportunity = &stored[0];
+        assert_eq!(opportunity.token_pair, "WETH/USDC");
+        assert_eq!((opportunity.buy_dex.as_str(), opportunity.sell_dex.as_str()), ("Synthetic A", "Synthetic B"));
+        assert_eq!(opportunity.price_difference, Decimal::from(18));
+        assert!(opportunity.profit_usd >= Decimal::ONE);
+        assert!(opportunity.profit_percentage >= Decimal::new(1, 1));
         
-        // Calculate profit
-        let gross_profit = sell_revenue - buy_cost;
-        let net_profit = gross_profit - gas_cost_usd;
-        let profit_percentage = if buy_cost > Decimal::ZERO {
-            (net_profit / buy_cost) * Decimal::from(100)
-        } else {
-            Decimal::ZERO
+        // With the minimum raised above the WETH/USDC spread nothing is stored
+        config.arbitrage.min_profit_percentage = 2.0;
+        let database = Database::new("sqlite::memory:").await.unwrap();
+        database.migrate().await.unwrap();
+        let mut detector = ArbitrageDetector::new(config, database.clone()).await.unwrap();
+        
+        assert!(detector.check_arbitrage_opportunities().await.unwrap().is_empty());
+        assert!(database.get_recent_opportunities(10).await.unwrap().is_empty());
+    }
+    
+    #[tokio::test]
+    async fn price_history_is_sampled_and_pruned() {
+        let mut config = test_config();
+        config.dexes.uniswap_v2.enabled = false;
+        config.dexes.quickswap.enabled = false;
+        config.dexes.sushiswap.enabled = false;
+        config.synthetic_dexes = vec![
+            synthetic_dex("Synthetic A", 1800.0, 34_000.0),
+            synthetic_dex("Synthetic B", 1818.0, 34_005.0),
+        ];
+        config.database.price_history.sample_every = 3;
+        config.database.price_history.retention_days = 7;
+        
+        let database = Database::new("sqlite::memory:").await.unwrap();
+        database.migrate().await.unwrap();
+        let expired = PriceQuote {
+            timestamp: Utc::now() - chrono::Duration::days(8),
+            ..quote("Synthetic A", "1790")
         };
+        database.save_price_snapshots(&[expired]).await.unwrap();
+        let mut detector = ArbitrageDetector::new(config, database.clone()).await.unwrap();
         
-        let price_difference = sell_quote.price - buy_quote.price;
+        let mut stored = Vec::new();
+        for _ in 0..6 {
+            detector.check_arbitrage_opportunities().await.unwrap();
+            let mut count = 0;
+            database.for_each_price_snapshot(None, None, None, None, |_| { count += 1; Ok(()) }).await.unwrap();
+            stored.push(count);
+        }
         
-        Ok(ArbitrageOpportunity {
-            id: None,
-            timestamp: Utc::now(),
-            token_pair: format!("{}/{}", token_pair.token0.symbol, token_pair.token1.symbol),
-            buy_dex: buy_quote.dex_name.clone(),
-            sell_dex: sell_quote.dex_name.clone(),
-            buy_price: buy_quote.price,
-            sell_price: sell_quote.price,
-            price_difference,
-            profit_usd: net_profit,
-            profit_percentage,
-            trade_amount: trade_amount_usd,
-            gas_cost_usd,
-        })
+        // The expired snapshot goes on the first cycle; then two pairs from two DEXes every third cycle
+        assert_eq!(stored, vec![0, 0, 4, 4, 4, 8]);
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,273 @@
+use crate::arbitrage::{evaluate_quotes, token_pairs};
+use crate::config::Config;
+use crate::database::{ArbitrageOpportunity, PriceSnapshot};
+use crate::dex::{DexManager, PriceQuote, TokenPair};
+use crate::errors::{ArbitrageError, Result};
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use tracing::{info, warn};
+
+// Pool reserves of one DEX at one block, in whole token units. reserve0 is
+// the pair's base token (WETH, WBTC) and reserve1 the quote token (USDC).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReserveSnapshot {
+    pub block: u64,
+    pub timestamp: DateTime<Utc>,
+    pub dex: String,
+    pub token_pair: String,
+    pub reserve0: Decimal,
+    pub reserve1: Decimal,
+    #[serde(default = "default_fee_bps")]
+    pub fee_bps: u32,
+}
+
+fn default_fee_bps() -> u32 {
+    30
+}
+
+impl ReserveSnapshot {
+    // Uniswap V2 getAmountOut for one unit of the base token
+    pub fn to_quote(&self) -> Option<PriceQuote> {
+        if self.reserve0 <= Decimal::ZERO || self.reserve1 <= Decimal::ZERO {
+            return None;
+        }
+        let amount_in_with_fee = Decimal::from(10_000 - self.fee_bps.min(10_000)) / Decimal::from(10_000);
+
+        Some(PriceQuote {
+            dex_name: self.dex.clone(),
+            token_pair: self.token_pair.clone(),
+            price: amount_in_with_fee * self.reserve1 / (self.reserve0 + amount_in_with_fee),
+            liquidity: self.reserve1,
+            timestamp: self.timestamp,
+            block_number: Some(self.block),
+            latency_ms: 0,
+        })
+    }
+}
+
+pub enum PoolStateSource {
+    Archive(DexManager),
+    Snapshots(Vec<ReserveSnapshot>),
+    PriceHistory(Vec<PriceSnapshot>),
+}
+
+impl PoolStateSource {
+    pub fn load_snapshots(path: &str) -> Result<Self> {
+        let content = fs::read_to_string(path)
+            .map_err(|e| ArbitrageError::Config(format!("Failed to read {}: {}", path, e)))?;
+        let snapshots: Vec<ReserveSnapshot> = serde_json::from_str(&content)
+            .map_err(|e| ArbitrageError::Config(format!("Invalid reserve snapshots in {}: {}", path, e)))?;
+        Ok(Self::Snapshots(snapshots))
+    }
+
+    // Quotes grouped by block for the recorded sources
+    fn recorded_quotes(&self, from_block: u64, to_block: u64) -> BTreeMap<u64, Vec<PriceQuote>> {
+        let mut by_block: BTreeMap<u64, Vec<PriceQuote>> = BTreeMap::new();
+        let in_range = |block: u64| block >= from_block && block <= to_block;
+
+        match self {
+            Self::Archive(_) => {}
+            Self::Snapshots(snapshots) => {
+                for snapshot in snapshots.iter().filter(|s| in_range(s.block)) {
+                    match snapshot.to_quote() {
+                        Some(quote) => by_block.entry(snapshot.block).or_default().push(quote),
+                        None => warn!(
+                            "Skipping empty {} pool on {} at block {}",
+                            snapshot.token_pair, snapshot.dex, snapshot.block
+                        ),
+                    }
+                }
+            }
+            Self::PriceHistory(snapshots) => {
+                for snapshot in snapshots {
+                    let Some(block) = snapshot.block_number.map(|block| block as u64).filter(|b| in_range(*b)) else {
+                        continue;
+                    };
+                    by_block.entry(block).or_default().push(PriceQuote {
+                        dex_name: snapshot.dex_name.clone(),
+                        token_pair: snapshot.token_pair.clone(),
+                        price: snapshot.price,
+                        liquidity: snapshot.liquidity,
+                        timestamp: snapshot.timestamp,
+                        block_number: Some(block),
+                        latency_ms: snapshot.latency_ms as u64,
+                    });
+                }
+            }
+        }
+
+        by_block
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct PairBacktestSummary {
+    pub token_pair: String,
+    pub opportunities: usize,
+    pub theoretical_pnl_usd: Decimal,
+    pub gas_spent_usd: Decimal,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BacktestReport {
+    pub from_block: u64,
+    pub to_block: u64,
+    pub blocks_evaluated: usize,
+    pub theoretical_pnl_usd: Decimal,
+    pub gas_spent_usd: Decimal,
+    pub pairs: Vec<PairBacktestSummary>,
+    pub rejections: BTreeMap<String, usize>,
+    pub opportunities: Vec<ArbitrageOpportunity>,
+}
+
+pub async fn run_backtest(config: &Config, source: &PoolStateSource, from_block: u64, to_block: u64, step: u64) -> Result<BacktestReport> {
+    if from_block > to_block {
+        return Err(ArbitrageError::Config(format!("Invalid block range {}..{}", from_block, to_block)));
+    }
+
+    let pairs = token_pairs(config);
+    let mut blocks_evaluated = 0;
+    let mut opportunities = Vec::new();
+    let mut rejections = BTreeMap::new();
+
+    match source {
+        PoolStateSource::Archive(dex_manager) => {
+            for block in (from_block..=to_block).step_by(step.max(1) as usize) {
+                let timestamp = dex_manager.get_block_timestamp(block).await?;
+                for token_pair in &pairs {
+                    let prices = dex_manager.get_all_prices_at(token_pair, block).await;
+                    evaluate_at(config, &prices, token_pair, timestamp, &mut opportunities, &mut rejections);
+                }
+                blocks_evaluated += 1;
+            }
+        }
+        PoolStateSource::Snapshots(_) | PoolStateSource::PriceHistory(_) => {
+            for (_, quotes) in source.recorded_quotes(from_block, to_block) {
+                for token_pair in &pairs {
+                    let pair_name = format!("{}/{}", token_pair.token0.symbol, token_pair.token1.symbol);
+                    let prices: Vec<PriceQuote> = quotes
+                        .iter()
+                        .filter(|quote| quote.token_pair == pair_name)
+                        .cloned()
+                        .collect();
+                    let Some(timestamp) = prices.iter().map(|p| p.timestamp).max() else {
+                        continue;
+                    };
+                    evaluate_at(config, &prices, token_pair, timestamp, &mut opportunities, &mut rejections);
+                }
+                blocks_evaluated += 1;
+            }
+        }
+    }
+
+    let mut pairs: Vec<PairBacktestSummary> = Vec::new();
+    for opportunity in &opportunities {
+        let summary = match pairs.iter_mut().position(|p| p.token_pair == opportunity.token_pair) {
+            Some(index) => &mut pairs[index],
+            None => {
+                pairs.push(PairBacktestSummary {
+                    token_pair: opportunity.token_pair.clone(),
+                    ..Default::default()
+                });
+                pairs.last_mut().unwrap()
+            }
+        };
+        summary.opportunities += 1;
+        summary.theoretical_pnl_usd += opportunity.profit_usd;
+        summary.gas_spent_usd += opportunity.gas_cost_usd;
+    }
+
+    let report = BacktestReport {
+        from_block,
+        to_block,
+        blocks_evaluated,
+        theoretical_pnl_usd: pairs.iter().map(|p| p.theoretical_pnl_usd).sum(),
+        gas_spent_usd: pairs.iter().map(|p| p.gas_spent_usd).sum(),
+        pairs,
+        rejections,
+        opportunities,
+    };
+
+    info!(
+        "Backtest of blocks {}..={} evaluated {} blocks and found {} opportunities",
+        from_block,
+        to_block,
+        report.blocks_evaluated,
+        report.opportunities.len()
+    );
+
+    Ok(report)
+}
+
+fn evaluate_at(
+    config: &Config,
+    prices: &[PriceQuote],
+    token_pair: &TokenPair,
+    timestamp: DateTime<Utc>,
+    opportunities: &mut Vec<ArbitrageOpportunity>,
+    rejections: &mut BTreeMap<String, usize>,
+) {
+    // Historical runs ignore the live wallet; trade size comes from config alone
+    let Some(evaluation) = evaluate_quotes(config, None, prices, token_pair, timestamp) else {
+        return;
+    };
+
+    match evaluation.rejection {
+        Some(reason) => *rejections.entry(reason.as_str().to_string()).or_default() += 1,
+        None => {
+            let mut opportunity = evaluation.spread;
+            opportunity.timestamp = timestamp;
+            opportunities.push(opportunity);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::tests::test_config;
+
+    #[tokio::test]
+    async fn replays_recorded_reserves() {
+        let config = test_config();
+        let source = PoolStateSource::load_snapshots(concat!(env!("CARGO_MANIFEST_DIR"), "/fixtures/backtest_reserves.json")).unwrap();
+
+        let report = run_backtest(&config, &source, 50_000_000, 50_000_002, 1).await.unwrap();
+        assert_eq!(report.blocks_evaluated, 3);
+
+        let routes: Vec<(i64, &str, &str, &str)> = report
+            .opportunities
+            .iter()
+            .map(|o| (o.timestamp.timestamp() % 60, o.token_pair.as_str(), o.buy_dex.as_str(), o.sell_dex.as_str()))
+            .collect();
+        assert_eq!(
+            routes,
+            vec![
+                (0, "WBTC/USDC", "Uniswap V2", "QuickSwap"),
+                (2, "WETH/USDC", "QuickSwap", "Uniswap V2"),
+                (2, "WBTC/USDC", "Uniswap V2", "QuickSwap"),
+                (4, "WETH/USDC", "QuickSwap", "Uniswap V2"),
+                (4, "WBTC/USDC", "QuickSwap", "Uniswap V2"),
+            ]
+        );
+        // 300k gas at 30 gwei and $0.80 per MATIC, for both swaps
+        assert!(report.opportunities.iter().all(|o| o.gas_cost_usd == Decimal::new(144, 4)));
+        assert_eq!(report.gas_spent_usd, Decimal::new(720, 4));
+        assert_eq!(report.opportunities[0].profit_usd.round_dp(6), Decimal::new(15_140_692, 6));
+
+        let pnl: Vec<(&str, usize, Decimal)> = report
+            .pairs
+            .iter()
+            .map(|p| (p.token_pair.as_str(), p.opportunities, p.theoretical_pnl_usd.round_dp(6)))
+            .collect();
+        assert_eq!(
+            pnl,
+            vec![("WBTC/USDC", 3, Decimal::new(38_740_033, 6)), ("WETH/USDC", 2, Decimal::new(10_300_309, 6))]
+        );
+        assert_eq!(report.theoretical_pnl_usd.round_dp(6), Decimal::new(49_040_342, 6));
+        assert_eq!(report.rejections, BTreeMap::from([("below_usd_threshold".to_string(), 1)]));
+    }
+}
@@ -1,5 +1,6 @@
-use anyhow::Result;
+use anyhow::{bail, Context, Result};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::fs;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -7,15 +8,62 @@ pub struct Config {
     pub general: GeneralConfig,
     pub polygon: PolygonConfig,
     pub dexes: DexesConfig,
+    #[serde(default)]
+    pub synthetic_dexes: Vec<SyntheticDexConfig>,
     pub tokens: TokensConfig,
     pub database: DatabaseConfig,
     pub arbitrage: ArbitrageConfig,
+    #[serde(default)]
+    pub submission: SubmissionConfig,
+    pub wallet: Option<WalletConfig>,
+    #[serde(default)]
+    pub approvals: ApprovalConfig,
+    #[serde(default)]
+    pub risk: RiskConfig,
+    #[serde(default)]
+    pub server: ServerConfig,
+    #[serde(default)]
+    pub health: HealthConfig,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GeneralConfig {
     pub check_interval: u64, // seconds
-    pub log_level: String,
+    pub log_level: String,   // "info", or per module: "warn,polygon_arbitrage_bot::arbitrage=debug"
+    #[serde(default)]
+    pub log_format: LogFormat,
+    pub log_file: Option<LogFileConfig>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LogFormat {
+    #[default]
+    Text,
+    Json,
+}
+
+// Written in addition to stdout
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LogFileConfig {
+    pub path: String,
+    #[serde(default)]
+    pub rotation: LogRotation,
+    #[serde(default = "default_max_log_files")]
+    pub max_files: usize, // rotated files kept, 0 keeps all
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LogRotation {
+    Hourly,
+    #[default]
+    Daily,
+    Never,
+}
+
+fn default_max_log_files() -> usize {
+    7
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -24,6 +72,24 @@ pub struct PolygonConfig {
     pub chain_id: u64,
     pub gas_price_gwei: f64,
     pub gas_limit: u64,
+    #[serde(default = "default_native_price_usd")]
+    pub native_price_usd: f64, // MATIC price used to value gas
+    #[serde(default)]
+    pub rpc_mode: RpcMode,
+    pub rpc_cassette: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RpcMode {
+    #[default]
+    Live,
+    Record,
+    Replay,
+}
+
+fn default_native_price_usd() -> f64 {
+    1.0
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -33,6 +99,12 @@ pub struct DexesConfig {
     pub sushiswap: DexConfig,
 }
 
+impl DexesConfig {
+    pub fn all(&self) -> Vec<&DexConfig> {
+        vec![&self.uniswap_v2, &self.quickswap, &self.sushiswap]
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DexConfig {
     pub name: String,
@@ -41,6 +113,65 @@ pub struct DexConfig {
     pub enabled: bool,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SyntheticDexConfig {
+    pub name: String,
+    pub enabled: bool,
+    #[serde(default = "default_synthetic_liquidity")]
+    pub liquidity: f64,
+    pub pairs: HashMap<String, PriceScript>, // "WETH/USDC" -> script
+}
+
+fn default_synthetic_liquidity() -> f64 {
+    1_000_000.0
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum PriceScript {
+    Static {
+        price: f64,
+    },
+    RandomWalk {
+        start: f64,
+        volatility_bps: u32,
+        #[serde(default)]
+        seed: u64,
+    },
+    Csv {
+        path: String,
+    },
+    Spikes {
+        base: f64,
+        spikes: Vec<PriceSpike>,
+    },
+}
+
+impl PriceScript {
+    pub fn initial_price(&self) -> f64 {
+        match self {
+            Self::Static { price } => *price,
+            Self::RandomWalk { start, .. } => *start,
+            Self::Csv { .. } => 0.0,
+            Self::Spikes { base, .. } => *base,
+        }
+    }
+    
+    pub fn seed(&self) -> u64 {
+        match self {
+            Self::RandomWalk { seed, .. } => *seed,
+            _ => 0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PriceSpike {
+    pub at_tick: u64,
+    pub duration_ticks: u64,
+    pub change_pct: f64,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TokensConfig {
     pub weth: TokenConfig,
@@ -48,6 +179,12 @@ pub struct TokensConfig {
     pub usdc: TokenConfig,
 }
 
+impl TokensConfig {
+    pub fn all(&self) -> Vec<&TokenConfig> {
+        vec![&self.weth, &self.wbtc, &self.usdc]
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TokenConfig {
     pub address: String,
@@ -58,6 +195,52 @@ pub struct TokenConfig {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DatabaseConfig {
     pub url: String,
+    #[serde(default)]
+    pub price_history: PriceHistoryConfig,
+    #[serde(default)]
+    pub retention: RetentionConfig,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PriceHistoryConfig {
+    pub enabled: bool,
+    pub sample_every: u32,   // store quotes from every Nth detection cycle
+    pub retention_days: u32, // 0 keeps snapshots forever
+}
+
+impl Default for PriceHistoryConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            sample_every: 1,
+            retention_days: 30,
+        }
+    }
+}
+
+// Limits for arbitrage_opportunities; opportunities with a recorded
+// execution are never pruned
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RetentionConfig {
+    pub max_age_days: u32,          // 0 keeps opportunities regardless of age
+    pub max_rows: u64,              // 0 means no row limit
+    pub downsample: bool,           // roll pruned rows into opportunity_hourly first
+    pub interval_secs: u64,         // how often the policy is applied
+    pub vacuum_interval_hours: u64, // 0 disables VACUUM and WAL checkpoints
+}
+
+impl Default for RetentionConfig {
+    fn default() -> Self {
+        Self {
+            max_age_days: 0,
+            max_rows: 0,
+            downsample: true,
+            interval_secs: 3600,
+            vacuum_interval_hours: 24,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -66,12 +249,303 @@ pub struct ArbitrageConfig {
     pub min_profit_percentage: f64,
     pub trade_amount_usd: f64,
     pub max_slippage: f64,
+    #[serde(default = "default_max_quote_age_secs")]
+    pub max_quote_age_secs: u64, // 0 disables the staleness check
+    #[serde(default)]
+    pub min_liquidity: f64, // quotes reporting zero (unknown) liquidity are not checked
+    #[serde(default)]
+    pub spread_log: SpreadLogConfig,
+}
+
+fn default_max_quote_age_secs() -> u64 {
+    30
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SpreadLogConfig {
+    pub enabled: bool,
+    pub sample_every: u32, // keep every Nth rejected spread
+}
+
+impl Default for SpreadLogConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            sample_every: 1,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SubmissionBackend {
+    Public,
+    PrivateRpc,
+    Bundle,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SubmissionConfig {
+    pub backend: SubmissionBackend,
+    pub private_rpc_url: Option<String>,
+    pub relay_url: Option<String>,
+    pub bundle_target_blocks: u64,
+    pub timeout_secs: u64,
+}
+
+impl Default for SubmissionConfig {
+    fn default() -> Self {
+        Self {
+            backend: SubmissionBackend::Public,
+            private_rpc_url: None,
+            relay_url: None,
+            bundle_target_blocks: 2,
+            timeout_secs: 10,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WalletConfig {
+    pub address: String,
+    pub private_key: Option<String>,
+    pub min_native_balance: f64, // MATIC kept aside for gas
+    pub snapshot_interval: u64,  // seconds
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ApprovalPolicy {
+    Exact,
+    Infinite,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApprovalConfig {
+    pub policy: ApprovalPolicy,
+    pub check_on_startup: bool,
+    pub submit_on_startup: bool,
+    pub gas_limit: u64,
+}
+
+impl Default for ApprovalConfig {
+    fn default() -> Self {
+        Self {
+            policy: ApprovalPolicy::Exact,
+            check_on_startup: true,
+            submit_on_startup: false,
+            gas_limit: 100_000,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RiskConfig {
+    pub enabled: bool,
+    pub max_notional_usd: f64,
+    pub max_daily_loss_usd: f64,
+    pub max_trades_per_hour: u32,
+    pub max_consecutive_reverts: u32,
+    pub max_token_exposure: HashMap<String, f64>, // token symbol -> units held from unfinished legs
+}
+
+impl Default for RiskConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            max_notional_usd: 10_000.0,
+            max_daily_loss_usd: 100.0,
+            max_trades_per_hour: 30,
+            max_consecutive_reverts: 3,
+            max_token_exposure: HashMap::new(),
+        }
+    }
+}
+
+// Environment variables under this prefix override file values; `__`
+// separates levels, so ARB_POLYGON__RPC_URL sets polygon.rpc_url
+pub const ENV_PREFIX: &str = "ARB_";
+
+// HTTP endpoint for Prometheus scraping and health probes, started by `run`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ServerConfig {
+    pub enabled: bool,
+    pub listen: String,
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            listen: "0.0.0.0:8080".to_string(),
+        }
+    }
+}
+
+// Thresholds for /healthz and /readyz
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HealthConfig {
+    pub max_cycle_age_secs: u64, // since the last successful detection cycle; 0 derives it from check_interval
+    pub max_quote_age_secs: u64, // since a fetcher last returned a quote; 0 derives it from check_interval
+    pub check_timeout_secs: u64, // per RPC and database probe
+}
+
+impl Default for HealthConfig {
+    fn default() -> Self {
+        Self {
+            max_cycle_age_secs: 0,
+            max_quote_age_secs: 0,
+            check_timeout_secs: 5,
+        }
+    }
 }
 
 impl Config {
-    pub fn load(path: &str) -> Result<Self> {
-        let content = fs::read_to_string(path)?;
-        let config: Config = toml::from_str(&content)?;
+    // Layers, lowest first: serde defaults, the TOML file, ARB_* environment
+    // variables, then `overrides` (dotted keys from the command line)
+    pub fn load(path: &str, overrides: &[(String, String)]) -> Result<Self> {
+        let content = fs::read_to_string(path).with_context(|| format!("cannot read {}", path))?;
+        let mut value: toml::Value = toml::from_str(&content).with_context(|| format!("cannot parse {}", path))?;
+        
+        let mut layers = env_overrides(std::env::vars());
+        layers.extend(overrides.iter().cloned());
+        for (key, raw) in &layers {
+            apply_override(&mut value, key, raw)?;
+        }
+        
+        let config: Config = value.try_into().with_context(|| format!("invalid configuration in {} after overrides", path))?;
+        
+        // serde ignores unknown keys, so a misspelt override would otherwise do nothing
+        let resolved = toml::Value::try_from(&config)?;
+        for (key, _) in &layers {
+            if lookup(&resolved, key).is_none() {
+                bail!("override {} does not match any configuration key", key);
+            }
+        }
+        
         Ok(config)
     }
-}
\ No newline at end of file
+}
+
+fn env_overrides(vars: impl IntoIterator<Item = (String, String)>) -> Vec<(String, String)> {
+    let mut overrides: Vec<(String, String)> = vars
+        .into_iter()
+        .filter_map(|(name, raw)| {
+            let path = name.strip_prefix(ENV_PREFIX)?;
+            let key = path.split("__").map(str::to_lowercase).collect::<Vec<_>>().join(".");
+            Some((key, raw))
+        })
+        .collect();
+    // The environment has no defined order; sort so repeated runs agree
+    overrides.sort();
+    overrides
+}
+
+// Tables match keys case-insensitively, since environment variable names are
+// upper case while map keys such as token symbols may not be
+fn find_key(table: &toml::Table, key: &str) -> Option<String> {
+    table.keys().find(|existing| existing.eq_ignore_ascii_case(key)).cloned()
+}
+
+fn lookup<'a>(value: &'a toml::Value, key: &str) -> Option<&'a toml::Value> {
+    key.split('.').try_fold(value, |current, segment| match current {
+        toml::Value::Table(table) => table.get(&find_key(table, segment)?),
+        toml::Value::Array(items) => items.get(segment.parse::<usize>().ok()?),
+        _ => None,
+    })
+}
+
+fn apply_override(root: &mut toml::Value, key: &str, raw: &str) -> Result<()> {
+    let segments: Vec<&str> = key.split('.').collect();
+    if segments.iter().any(|segment| segment.is_empty()) {
+        bail!("invalid override key {:?}", key);
+    }
+    
+    let (last, parents) = segments.split_last().expect("split yields at least one segment");
+    let mut current = root;
+    for segment in parents {
+        current = match current {
+            toml::Value::Table(table) => {
+                let name = find_key(table, segment).unwrap_or_else(|| segment.to_string());
+                table.entry(name).or_insert_with(|| toml::Value::Table(toml::Table::new()))
+            }
+            toml::Value::Array(items) => segment
+                .parse::<usize>()
+                .ok()
+                .and_then(|index| items.get_mut(index))
+                .with_context(|| format!("override {}: no element {} in the array", key, segment))?,
+            _ => bail!("override {}: {} is not a table", key, segment),
+        };
+    }
+    
+    let toml::Value::Table(table) = current else {
+        bail!("override {}: parent is not a table", key);
+    };
+    let name = find_key(table, last).unwrap_or_else(|| last.to_string());
+    let value = parse_value(raw, table.get(&name));
+    table.insert(name, value);
+    Ok(())
+}
+
+// Keeps strings as strings (URLs, keys, addresses); anything else is read as
+// a TOML value so numbers and booleans get their proper types. 0x literals
+// are always strings, or a private key given only in the environment would
+// parse as a hex integer.
+fn parse_value(raw: &str, existing: Option<&toml::Value>) -> toml::Value {
+    if matches!(existing, Some(toml::Value::String(_))) || raw.starts_with("0x") {
+        return toml::Value::String(raw.to_string());
+    }
+    toml::from_str::<toml::Table>(&format!("value = {}", raw))
+        .ok()
+        .and_then(|mut table| table.remove("value"))
+        .unwrap_or_else(|| toml::Value::String(raw.to_string()))
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    
+    // fixtures/config.toml, without environment or command-line layers
+    pub(crate) fn test_config() -> Config {
+        toml::from_str(include_str!("../fixtures/config.toml")).unwrap()
+    }
+    
+    #[test]
+    fn overrides_are_layered_by_key_path() {
+        let mut value: toml::Value = toml::from_str(
+            r#"
+            [polygon]
+            rpc_url = "http://file"
+            chain_id = 137
+            
+            [risk.max_token_exposure]
+            WETH = 1.0
+            "#,
+        )
+        .unwrap();
+        
+        let layers = env_overrides(vec![
+            ("ARB_POLYGON__RPC_URL".to_string(), "https://env".to_string()),
+            ("ARB_POLYGON__CHAIN_ID".to_string(), "80001".to_string()),
+            ("ARB_WALLET__PRIVATE_KEY".to_string(), "0xabc".to_string()),
+            ("ARB_RISK__MAX_TOKEN_EXPOSURE__WETH".to_string(), "2.5".to_string()),
+            ("PATH".to_string(), "/usr/bin".to_string()),
+        ]);
+        assert_eq!(layers.len(), 4);
+        for (key, raw) in &layers {
+            apply_override(&mut value, key, raw).unwrap();
+        }
+        // The command line applies last and wins over the environment
+        apply_override(&mut value, "polygon.rpc_url", "https://cli").unwrap();
+        
+        assert_eq!(lookup(&value, "polygon.rpc_url").unwrap().as_str(), Some("https://cli"));
+        assert_eq!(lookup(&value, "polygon.chain_id").unwrap().as_integer(), Some(80001));
+        assert_eq!(lookup(&value, "wallet.private_key").unwrap().as_str(), Some("0xabc"));
+        assert_eq!(value["risk"]["max_token_exposure"]["WETH"].as_float(), Some(2.5));
+        assert!(apply_override(&mut value, "polygon.rpc_url.scheme", "x").is_err());
+    }
+}
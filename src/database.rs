@@ -1,8 +1,51 @@
-use sqlx::{SqlitePool, Row};
-use anyhow::Result;
-use chrono::{DateTime, Utc};
+use futures::TryStreamExt;
+use sqlx::{Executor, PgPool, Row, SqlitePool};
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, DurationRound, Utc};
 use rust_decimal::Decimal;
 use serde::{Serialize, Deserialize};
+use std::collections::BTreeMap;
+use crate::arbitrage::RejectionReason;
+use crate::dex::PriceQuote;
+use crate::wallet::WalletBalances;
+use tracing::info;
+
+// Forward-only schema migrations, applied in order and recorded in
+// `schema_version`. Never edit a migration once released; add a new one.
+// Every migration exists in both dialects under the same number.
+// The early ones use IF NOT EXISTS so databases created before versioning
+// are adopted without changes.
+struct Migration {
+    version: i64,
+    name: &'static str,
+    sqlite: &'static str,
+    postgres: &'static str,
+}
+
+macro_rules! migration {
+    ($version:literal, $prefix:literal, $name:literal) => {
+        Migration {
+            version: $version,
+            name: $name,
+            sqlite: include_str!(concat!("../migrations/sqlite/", $prefix, "_", $name, ".sql")),
+            postgres: include_str!(concat!("../migrations/postgres/", $prefix, "_", $name, ".sql")),
+        }
+    };
+}
+
+const MIGRATIONS: &[Migration] = &[
+    migration!(1, "0001", "arbitrage_opportunities"),
+    migration!(2, "0002", "wallet_balances"),
+    migration!(3, "0003", "executions"),
+    migration!(4, "0004", "price_snapshots"),
+    migration!(5, "0005", "evaluated_spreads"),
+    migration!(6, "0006", "risk_state"),
+    migration!(7, "0007", "opportunity_lifecycles"),
+    migration!(8, "0008", "decimal_text"),
+    migration!(9, "0009", "opportunity_hourly"),
+    migration!(10, "0010", "health_heartbeat"),
+    migration!(11, "0011", "risk_reset"),
+];
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ArbitrageOpportunity {
@@ -20,18 +63,1388 @@ pub struct ArbitrageOpportunity {
     pub gas_cost_usd: Decimal,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Execution {
+    pub id: Option<i64>,
+    pub opportunity_id: i64,
+    pub timestamp: DateTime<Utc>,
+    pub tx_hashes: Vec<String>,
+    pub block_number: Option<i64>,
+    pub status: String,
+    pub quote_token: String,
+    pub quote_spent: Decimal,
+    pub quote_received: Decimal,
+    pub base_token: String,
+    pub base_residual: Decimal,
+    pub swap_count: i64,
+    pub gas_used: i64,
+    pub gas_cost_native: Decimal,
+    pub gas_cost_usd: Decimal,
+    pub realised_pnl_usd: Decimal,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RouteExecutionReport {
+    pub token_pair: String,
+    pub buy_dex: String,
+    pub sell_dex: String,
+    pub executions: i64,
+    pub reverted: i64,
+    pub estimated_profit_usd: Decimal,
+    pub realised_pnl_usd: Decimal,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PriceSnapshot {
+    pub id: Option<i64>,
+    pub timestamp: DateTime<Utc>,
+    pub dex_name: String,
+    pub token_pair: String,
+    pub block_number: Option<i64>,
+    pub price: Decimal,
+    pub liquidity: Decimal,
+    pub latency_ms: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OpportunityLifecycle {
+    pub id: Option<i64>,
+    pub token_pair: String,
+    pub buy_dex: String,
+    pub sell_dex: String,
+    pub first_seen: DateTime<Utc>,
+    pub last_seen: DateTime<Utc>,
+    pub first_block: Option<i64>,
+    pub last_block: Option<i64>,
+    pub observations: i64,
+    pub initial_profit_usd: Decimal,
+    pub peak_profit_usd: Decimal,
+    pub last_profit_usd: Decimal,
+    pub close_reason: Option<String>,
+    pub closed_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MigrationStatus {
+    pub version: i64,
+    pub name: String,
+    pub applied_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Default)]
+struct HourlyBucket {
+    opportunities: i64,
+    total_profit_usd: Decimal,
+    max_profit_usd: Option<Decimal>,
+}
+
+impl HourlyBucket {
+    fn add(&mut self, opportunities: i64, total_profit_usd: Decimal, max_profit_usd: Decimal) {
+        self.opportunities += opportunities;
+        self.total_profit_usd += total_profit_usd;
+        self.max_profit_usd = Some(self.max_profit_usd.map_or(max_profit_usd, |max| max.max(max_profit_usd)));
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct RouteProfits {
+    pub token_pair: String,
+    pub buy_dex: String,
+    pub sell_dex: String,
+    pub profits: Vec<Decimal>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OpportunityOrder {
+    #[default]
+    Newest,
+    Oldest,
+    MostProfitable,
+}
+
+impl OpportunityOrder {
+    fn sql(self) -> &'static str {
+        match self {
+            OpportunityOrder::Newest => "o.timestamp DESC, o.id DESC",
+            OpportunityOrder::Oldest => "o.timestamp ASC, o.id ASC",
+            OpportunityOrder::MostProfitable => "CAST(o.profit_usd AS NUMERIC) DESC, o.id DESC",
+        }
+    }
+}
+
+impl std::str::FromStr for OpportunityOrder {
+    type Err = String;
+    
+    fn from_str(value: &str) -> std::result::Result<Self, Self::Err> {
+        match value {
+            "newest" => Ok(OpportunityOrder::Newest),
+            "oldest" => Ok(OpportunityOrder::Oldest),
+            "most_profitable" | "most-profitable" => Ok(OpportunityOrder::MostProfitable),
+            _ => Err(format!("unknown order {:?}: expected newest, oldest or most-profitable", value)),
+        }
+    }
+}
+
+// Filters for `Database::query_opportunities`. Every filter is optional;
+// deserializable so an HTTP API can take it straight from query parameters.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct OpportunityQuery {
+    token_pair: Option<String>,
+    buy_dex: Option<String>,
+    sell_dex: Option<String>,
+    from: Option<DateTime<Utc>>,
+    to: Option<DateTime<Utc>>,
+    min_profit_usd: Option<Decimal>,
+    executed: Option<bool>,
+    order: OpportunityOrder,
+    limit: i64,
+    offset: i64,
+}
+
+impl Default for OpportunityQuery {
+    fn default() -> Self {
+        Self {
+            token_pair: None,
+            buy_dex: None,
+            sell_dex: None,
+            from: None,
+            to: None,
+            min_profit_usd: None,
+            executed: None,
+            order: OpportunityOrder::Newest,
+            limit: 100,
+            offset: 0,
+        }
+    }
+}
+
+impl OpportunityQuery {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    pub fn token_pair(mut self, token_pair: impl Into<String>) -> Self {
+        self.token_pair = Some(token_pair.into());
+        self
+    }
+    
+    pub fn buy_dex(mut self, dex_name: impl Into<String>) -> Self {
+        self.buy_dex = Some(dex_name.into());
+        self
+    }
+    
+    pub fn sell_dex(mut self, dex_name: impl Into<String>) -> Self {
+        self.sell_dex = Some(dex_name.into());
+        self
+    }
+    
+    pub fn since(mut self, from: DateTime<Utc>) -> Self {
+        self.from = Some(from);
+        self
+    }
+    
+    pub fn until(mut self, to: DateTime<Utc>) -> Self {
+        self.to = Some(to);
+        self
+    }
+    
+    pub fn min_profit_usd(mut self, profit: Decimal) -> Self {
+        self.min_profit_usd = Some(profit);
+        self
+    }
+    
+    pub fn executed(mut self, executed: bool) -> Self {
+        self.executed = Some(executed);
+        self
+    }
+    
+    pub fn order(mut self, order: OpportunityOrder) -> Self {
+        self.order = order;
+        self
+    }
+    
+    pub fn limit(mut self, limit: i64) -> Self {
+        self.limit = limit;
+        self
+    }
+    
+    pub fn offset(mut self, offset: i64) -> Self {
+        self.offset = offset;
+        self
+    }
+}
+
+// Decimals are stored as TEXT so they round-trip exactly. Rows converted from
+// the old float columns may be in scientific notation.
+fn parse_decimal(value: &str) -> Result<Decimal> {
+    value
+        .parse::<Decimal>()
+        .or_else(|_| Decimal::from_scientific(value))
+        .with_context(|| format!("Invalid decimal value {:?}", value))
+}
+
+#[derive(Clone)]
+enum DbPool {
+    Sqlite(SqlitePool),
+    Postgres(PgPool),
+}
+
+// Runs the same query code against whichever pool is configured. All SQL
+// uses `$N` placeholders and `RETURNING`, which both drivers accept; sqlx
+// resolves the row and argument types separately for each arm.
+// `INSERT ... RETURNING` uses fetch_all: SQLite only commits the insert once
+// the statement has run to completion, which fetch_one does not guarantee.
+macro_rules! with_pool {
+    ($self:ident, $pool:ident => $body:expr) => {
+        match &$self.pool {
+            DbPool::Sqlite($pool) => $body,
+            DbPool::Postgres($pool) => $body,
+        }
+    };
+}
+
+macro_rules! price_snapshot_from_row {
+    ($row:expr) => {
+        PriceSnapshot {
+            id: Some($row.get("id")),
+            timestamp: $row.get("timestamp"),
+            dex_name: $row.get("dex_name"),
+            token_pair: $row.get("token_pair"),
+            block_number: $row.get("block_number"),
+            price: parse_decimal($row.get("price"))?,
+            liquidity: parse_decimal($row.get("liquidity"))?,
+            latency_ms: $row.get("latency_ms"),
+        }
+    };
+}
+
+macro_rules! opportunity_from_row {
+    ($row:expr) => {
+        ArbitrageOpportunity {
+            id: Some($row.get("id")),
+            timestamp: $row.get("timestamp"),
+            token_pair: $row.get("token_pair"),
+            buy_dex: $row.get("buy_dex"),
+            sell_dex: $row.get("sell_dex"),
+            buy_price: parse_decimal($row.get("buy_price"))?,
+            sell_price: parse_decimal($row.get("sell_price"))?,
+            price_difference: parse_decimal($row.get("price_difference"))?,
+            profit_usd: parse_decimal($row.get("profit_usd"))?,
+            profit_percentage: parse_decimal($row.get("profit_percentage"))?,
+            trade_amount: parse_decimal($row.get("trade_amount"))?,
+            gas_cost_usd: parse_decimal($row.get("gas_cost_usd"))?,
+        }
+    };
+}
+
+// Cheap to clone: both pools are reference counted
+#[derive(Clone)]
 pub struct Database {
-    pool: SqlitePool,
+    pool: DbPool,
 }
 
 impl Database {
+    // The backend is picked from the URL scheme: sqlite:... or postgres://...
     pub async fn new(database_url: &str) -> Result<Self> {
-        let pool = SqlitePool::connect(database_url).await?;
+        let pool = if database_url.starts_with("postgres://") || database_url.starts_with("postgresql://") {
+            DbPool::Postgres(PgPool::connect(database_url).await?)
+        } else if database_url.starts_with("sqlite:") {
+            DbPool::Sqlite(SqlitePool::connect(database_url).await?)
+        } else {
+            bail!("Unsupported database URL {}: expected sqlite: or postgres://", database_url);
+        };
+        
         Ok(Self { pool })
     }
     
-    pub async fn migrate(&self) -> Result<()> {
-        sqlx::query(
+    pub fn backend(&self) -> &'static str {
+        match self.pool {
+            DbPool::Sqlite(_) => "sqlite",
+            DbPool::Postgres(_) => "postgres",
+        }
+    }
+    
+    pub async fn migrate(&self) -> Result<usize> {
+        let schema_version = match self.pool {
+            DbPool::Sqlite(_) => "CREATE TABLE IF NOT EXISTS schema_version (version INTEGER PRIMARY KEY, name TEXT NOT NULL, applied_at DATETIME NOT NULL)",
+            DbPool::Postgres(_) => "CREATE TABLE IF NOT EXISTS schema_version (version BIGINT PRIMARY KEY, name TEXT NOT NULL, applied_at TIMESTAMPTZ NOT NULL)",
+        };
+        with_pool!(self, pool => {
+            pool.execute(schema_version).await?;
+        });
+        
+        let current = self.current_schema_version().await?;
+        let latest = MIGRATIONS.last().map_or(0, |migration| migration.version);
+        if current > latest {
+            bail!("Database schema version {} is newer than this binary supports ({})", current, latest);
+        }
+        
+        let mut applied = 0;
+        for migration in MIGRATIONS.iter().filter(|migration| migration.version > current) {
+            let sql = match self.pool {
+                DbPool::Sqlite(_) => migration.sqlite,
+                DbPool::Postgres(_) => migration.postgres,
+            };
+            
+            // Each migration and its version row commit together, so a failure leaves the previous version intact
+            with_pool!(self, pool => {
+                let mut tx = pool.begin().await?;
+                (&mut *tx)
+                    .execute(sql)
+                    .await
+                    .with_context(|| format!("Migration {:04}_{} failed", migration.version, migration.name))?;
+                sqlx::query("INSERT INTO schema_version (version, name, applied_at) VALUES ($1, $2, $3)")
+                    .bind(migration.version)
+                    .bind(migration.name)
+                    .bind(Utc::now())
+                    .execute(&mut *tx)
+                    .await?;
+                tx.commit().await?;
+            });
+            
+            info!("Applied migration {:04}_{}", migration.version, migration.name);
+            applied += 1;
+        }
+        
+        Ok(applied)
+    }
+    
+    async fn current_schema_version(&self) -> Result<i64> {
+        with_pool!(self, pool => {
+            let row = sqlx::query("SELECT COALESCE(MAX(version), 0) AS version FROM schema_version")
+                .fetch_one(pool)
+                .await?;
+            Ok(row.get("version"))
+        })
+    }
+    
+    pub async fn migration_status(&self) -> Result<Vec<MigrationStatus>> {
+        // Read-only: a database that has never been migrated has no schema_version table yet
+        let tracked = match &self.pool {
+            DbPool::Sqlite(pool) => sqlx::query("SELECT name FROM sqlite_master WHERE type = 'table' AND name = 'schema_version'")
+                .fetch_optional(pool)
+                .await?
+                .is_some(),
+            DbPool::Postgres(pool) => sqlx::query("SELECT table_name FROM information_schema.tables WHERE table_name = 'schema_version'")
+                .fetch_optional(pool)
+                .await?
+                .is_some(),
+        };
+        
+        let mut applied: Vec<(i64, String, DateTime<Utc>)> = Vec::new();
+        if tracked {
+            applied = with_pool!(self, pool => {
+                sqlx::query("SELECT version, name, applied_at FROM schema_version ORDER BY version")
+                    .fetch_all(pool)
+                    .await?
+                    .into_iter()
+                    .map(|row| (row.get("version"), row.get("name"), row.get("applied_at")))
+                    .collect()
+            });
+        }
+        
+        let mut statuses: Vec<MigrationStatus> = MIGRATIONS
+            .iter()
+            .map(|migration| MigrationStatus {
+                version: migration.version,
+                name: migration.name.to_string(),
+                applied_at: applied
+                    .iter()
+                    .find(|(version, _, _)| *version == migration.version)
+                    .map(|(_, _, applied_at)| *applied_at),
+            })
+            .collect();
+        
+        // Versions recorded by a newer binary are still worth showing
+        for (version, name, applied_at) in applied {
+            if !MIGRATIONS.iter().any(|migration| migration.version == version) {
+                statuses.push(MigrationStatus { version, name, applied_at: Some(applied_at) });
+            }
+        }
+        
+        Ok(statuses)
+    }
+    
+    pub async fn save_opportunity(&self, opportunity: &ArbitrageOpportunity) -> Result<i64> {
+        with_pool!(self, pool => {
+            let rows = sqlx::query(
+                r#"
+                INSERT INTO arbitrage_opportunities (
+                    timestamp, token_pair, buy_dex, sell_dex, buy_price, sell_price,
+                    price_difference, profit_usd, profit_percentage, trade_amount, gas_cost_usd
+                )
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
+                RETURNING id
+                "#,
+            )
+            .bind(opportunity.timestamp)
+            .bind(&opportunity.token_pair)
+            .bind(&opportunity.buy_dex)
+            .bind(&opportunity.sell_dex)
+            .bind(opportunity.buy_price.to_string())
+            .bind(opportunity.sell_price.to_string())
+            .bind(opportunity.price_difference.to_string())
+            .bind(opportunity.profit_usd.to_string())
+            .bind(opportunity.profit_percentage.to_string())
+            .bind(opportunity.trade_amount.to_string())
+            .bind(opportunity.gas_cost_usd.to_string())
+            .fetch_all(pool)
+            .await?;
+            
+            Ok(rows[0].get("id"))
+        })
+    }
+    
+    #[allow(dead_code)]
+    pub async fn get_recent_opportunities(&self, limit: i64) -> Result<Vec<ArbitrageOpportunity>> {
+        self.query_opportunities(&OpportunityQuery::new().limit(limit)).await
+    }
+    
+    pub async fn query_opportunities(&self, query: &OpportunityQuery) -> Result<Vec<ArbitrageOpportunity>> {
+        let mut opportunities = Vec::new();
+        self.for_each_opportunity(query, |opportunity| {
+            opportunities.push(opportunity);
+            Ok(())
+        })
+        .await?;
+        
+        Ok(opportunities)
+    }
+    
+    // Streams matching rows to `sink` one at a time, so large exports never
+    // hold the whole result set in memory
+    pub async fn for_each_opportunity<F>(&self, query: &OpportunityQuery, mut sink: F) -> Result<()>
+    where
+        F: FnMut(ArbitrageOpportunity) -> Result<()>,
+    {
+        // Unset filters bind NULL and match everything. Profit is stored as
+        // TEXT, so it is cast for comparison and ordering only.
+        let sql = format!(
+            r#"
+            SELECT o.* FROM arbitrage_opportunities o
+            WHERE ($1 IS NULL OR o.token_pair = $1)
+              AND ($2 IS NULL OR o.buy_dex = $2)
+              AND ($3 IS NULL OR o.sell_dex = $3)
+              AND ($4 IS NULL OR o.timestamp >= $4)
+              AND ($5 IS NULL OR o.timestamp <= $5)
+              AND ($6 IS NULL OR CAST(o.profit_usd AS NUMERIC) >= CAST($6 AS NUMERIC))
+              AND ($7 IS NULL OR EXISTS (SELECT 1 FROM executions e WHERE e.opportunity_id = o.id) = $7)
+            ORDER BY {}
+            LIMIT $8 OFFSET $9
+            "#,
+            query.order.sql(),
+        );
+        
+        with_pool!(self, pool => {
+            let mut rows = sqlx::query(&sql)
+                .bind(query.token_pair.as_deref())
+                .bind(query.buy_dex.as_deref())
+                .bind(query.sell_dex.as_deref())
+                .bind(query.from)
+                .bind(query.to)
+                .bind(query.min_profit_usd.map(|profit| profit.to_string()))
+                .bind(query.executed)
+                .bind(query.limit)
+                .bind(query.offset)
+                .fetch(pool);
+            
+            while let Some(row) = rows.try_next().await? {
+                sink(opportunity_from_row!(row))?;
+            }
+        });
+        
+        Ok(())
+    }
+    
+    // Profits of every opportunity in the window, grouped by pair and route
+    // and sorted ascending so percentiles can be read off directly
+    pub async fn get_route_profits(&self, from: DateTime<Utc>, to: DateTime<Utc>) -> Result<Vec<RouteProfits>> {
+        let rows: Vec<(String, String, String, String)> = with_pool!(self, pool => {
+            sqlx::query(
+                r#"
+                SELECT token_pair, buy_dex, sell_dex, profit_usd
+                FROM arbitrage_opportunities
+                WHERE timestamp >= $1 AND timestamp <= $2
+                ORDER BY token_pair, buy_dex, sell_dex
+                "#,
+            )
+            .bind(from)
+            .bind(to)
+            .fetch_all(pool)
+            .await?
+            .into_iter()
+            .map(|row| (row.get("token_pair"), row.get("buy_dex"), row.get("sell_dex"), row.get("profit_usd")))
+            .collect()
+        });
+        
+        let mut routes: Vec<RouteProfits> = Vec::new();
+        for (token_pair, buy_dex, sell_dex, profit) in rows {
+            let profit = parse_decimal(&profit)?;
+            match routes.last_mut() {
+                Some(route) if route.token_pair == token_pair && route.buy_dex == buy_dex && route.sell_dex == sell_dex => {
+                    route.profits.push(profit);
+                }
+                _ => routes.push(RouteProfits { token_pair, buy_dex, sell_dex, profits: vec![profit] }),
+            }
+        }
+        
+        for route in &mut routes {
+            route.profits.sort();
+        }
+        
+        Ok(routes)
+    }
+    
+    // Opportunity count per UTC hour of day (0-23); hours without any are omitted
+    pub async fn count_opportunities_by_hour(&self, from: DateTime<Utc>, to: DateTime<Utc>) -> Result<Vec<(u32, i64)>> {
+        // Hours are UTC on both backends, whatever the session time zone
+        let hour = match self.pool {
+            DbPool::Sqlite(_) => "CAST(strftime('%H', timestamp) AS INTEGER)",
+            DbPool::Postgres(_) => "CAST(EXTRACT(HOUR FROM timestamp AT TIME ZONE 'UTC') AS BIGINT)",
+        };
+        let sql = format!(
+            r#"
+            SELECT {} AS hour, COUNT(*) AS opportunities
+            FROM arbitrage_opportunities
+            WHERE timestamp >= $1 AND timestamp <= $2
+            GROUP BY 1
+            ORDER BY 1
+            "#,
+            hour,
+        );
+        
+        with_pool!(self, pool => {
+            let rows = sqlx::query(&sql)
+                .bind(from)
+                .bind(to)
+                .fetch_all(pool)
+                .await?;
+            
+            Ok(rows
+                .iter()
+                .map(|row| (row.get::<i64, _>("hour") as u32, row.get("opportunities")))
+                .collect())
+        })
+    }
+    
+    pub async fn save_balance_snapshot(&self, balances: &WalletBalances) -> Result<()> {
+        with_pool!(self, pool => {
+            let mut tx = pool.begin().await?;
+            
+            // Native balance is stored under the gas token symbol with no address
+            sqlx::query(
+                r#"
+                INSERT INTO wallet_balances (timestamp, wallet_address, token_symbol, token_address, balance)
+                VALUES ($1, $2, 'MATIC', NULL, $3)
+                "#,
+            )
+            .bind(balances.timestamp)
+            .bind(&balances.wallet_address)
+            .bind(balances.native_balance.to_string())
+            .execute(&mut *tx)
+            .await?;
+            
+            for token in &balances.tokens {
+                sqlx::query(
+                    r#"
+                    INSERT INTO wallet_balances (timestamp, wallet_address, token_symbol, token_address, balance)
+                    VALUES ($1, $2, $3, $4, $5)
+                    "#,
+                )
+                .bind(balances.timestamp)
+                .bind(&balances.wallet_address)
+                .bind(&token.symbol)
+                .bind(&token.address)
+                .bind(token.balance.to_string())
+                .execute(&mut *tx)
+                .await?;
+            }
+            
+            tx.commit().await?;
+        });
+        
+        Ok(())
+    }
+    
+    pub async fn get_opportunity_route(&self, id: i64) -> Result<Option<(String, Decimal)>> {
+        with_pool!(self, pool => {
+            let row = sqlx::query(
+                r#"
+                SELECT token_pair, sell_price
+                FROM arbitrage_opportunities
+                WHERE id = $1
+                "#,
+            )
+            .bind(id)
+            .fetch_optional(pool)
+            .await?;
+            
+            match row {
+                Some(row) => {
+                    Ok(Some((row.get("token_pair"), parse_decimal(row.get("sell_price"))?)))
+                }
+                None => Ok(None),
+            }
+        })
+    }
+    
+    pub async fn save_execution(&self, execution: &Execution) -> Result<i64> {
+        with_pool!(self, pool => {
+            let rows = sqlx::query(
+                r#"
+                INSERT INTO executions (
+                    opportunity_id, timestamp, tx_hashes, block_number, status,
+                    quote_token, quote_spent, quote_received, base_token, base_residual,
+                    swap_count, gas_used, gas_cost_native, gas_cost_usd, realised_pnl_usd
+                )
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15)
+                RETURNING id
+                "#,
+            )
+            .bind(execution.opportunity_id)
+            .bind(execution.timestamp)
+            .bind(execution.tx_hashes.join(","))
+            .bind(execution.block_number)
+            .bind(&execution.status)
+            .bind(&execution.quote_token)
+            .bind(execution.quote_spent.to_string())
+            .bind(execution.quote_received.to_string())
+            .bind(&execution.base_token)
+            .bind(execution.base_residual.to_string())
+            .bind(execution.swap_count)
+            .bind(execution.gas_used)
+            .bind(execution.gas_cost_native.to_string())
+            .bind(execution.gas_cost_usd.to_string())
+            .bind(execution.realised_pnl_usd.to_string())
+            .fetch_all(pool)
+            .await?;
+            
+            Ok(rows[0].get("id"))
+        })
+    }
+    
+    pub async fn get_execution_report(&self) -> Result<Vec<RouteExecutionReport>> {
+        let rows: Vec<(String, String, String, String, String, String)> = with_pool!(self, pool => {
+            sqlx::query(
+                r#"
+                SELECT o.token_pair, o.buy_dex, o.sell_dex, e.status,
+                       o.profit_usd AS estimated_profit_usd,
+                       e.realised_pnl_usd
+                FROM executions e
+                JOIN arbitrage_opportunities o ON o.id = e.opportunity_id
+                ORDER BY o.token_pair, o.buy_dex, o.sell_dex
+                "#,
+            )
+            .fetch_all(pool)
+            .await?
+            .into_iter()
+            .map(|row| {
+                (
+                    row.get("token_pair"),
+                    row.get("buy_dex"),
+                    row.get("sell_dex"),
+                    row.get("status"),
+                    row.get("estimated_profit_usd"),
+                    row.get("realised_pnl_usd"),
+                )
+            })
+            .collect()
+        });
+        
+        // Sum in Rust so decimal amounts never pass through database floats
+        let mut reports: Vec<RouteExecutionReport> = Vec::new();
+        for (token_pair, buy_dex, sell_dex, status, estimated, realised) in rows {
+            let index = match reports
+                .iter()
+                .position(|r| r.token_pair == token_pair && r.buy_dex == buy_dex && r.sell_dex == sell_dex)
+            {
+                Some(index) => index,
+                None => {
+                    reports.push(RouteExecutionReport {
+                        token_pair,
+                        buy_dex,
+                        sell_dex,
+                        executions: 0,
+                        reverted: 0,
+                        estimated_profit_usd: Decimal::ZERO,
+                        realised_pnl_usd: Decimal::ZERO,
+                    });
+                    reports.len() - 1
+                }
+            };
+            
+            let report = &mut reports[index];
+            report.executions += 1;
+            if status != "success" {
+                report.reverted += 1;
+            }
+            report.estimated_profit_usd += parse_decimal(&estimated)?;
+            report.realised_pnl_usd += parse_decimal(&realised)?;
+        }
+        
+        Ok(reports)
+    }
+    
+    pub async fn count_executions_since(&self, since: DateTime<Utc>) -> Result<i64> {
+        with_pool!(self, pool => {
+            let row = sqlx::query("SELECT COUNT(*) AS count FROM executions WHERE timestamp >= $1")
+                .bind(since)
+                .fetch_one(pool)
+                .await?;
+            
+            Ok(row.get("count"))
+        })
+    }
+    
+    pub async fn get_realised_pnl_since(&self, since: DateTime<Utc>) -> Result<Decimal> {
+        let values: Vec<String> = with_pool!(self, pool => {
+            sqlx::query("SELECT realised_pnl_usd FROM executions WHERE timestamp >= $1")
+                .bind(since)
+                .fetch_all(pool)
+                .await?
+                .into_iter()
+                .map(|row| row.get("realised_pnl_usd"))
+                .collect()
+        });
+        
+        let mut total = Decimal::ZERO;
+        for value in values {
+            total += parse_decimal(&value)?;
+        }
+        
+        Ok(total)
+    }
+    
+    pub async fn get_recent_execution_statuses(&self, limit: i64) -> Result<Vec<String>> {
+        with_pool!(self, pool => {
+            let rows = sqlx::query("SELECT status FROM executions ORDER BY timestamp DESC, id DESC LIMIT $1")
+                .bind(limit)
+                .fetch_all(pool)
+                .await?;
+            
+            Ok(rows.into_iter().map(|row| row.get("status")).collect())
+        })
+    }
+    
+    pub async fn get_token_residuals_since(&self, since: Option<DateTime<Utc>>) -> Result<Vec<(String, Decimal)>> {
+        let rows: Vec<(String, String)> = with_pool!(self, pool => {
+            sqlx::query("SELECT base_token, base_residual FROM executions WHERE $1 IS NULL OR timestamp > $1")
+                .bind(since)
+                .fetch_all(pool)
+                .await?
+                .into_iter()
+                .map(|row| (row.get("base_token"), row.get("base_residual")))
+                .collect()
+        });
+        
+        let mut residuals: Vec<(String, Decimal)> = Vec::new();
+        for (token, amount) in rows {
+            let amount = parse_decimal(&amount)?;
+            match residuals.iter_mut().find(|(symbol, _)| *symbol == token) {
+                Some((_, total)) => *total += amount,
+                None => residuals.push((token, amount)),
+            }
+        }
+        
+        Ok(residuals)
+    }
+    
+    pub async fn get_kill_switch(&self) -> Result<Option<(String, DateTime<Utc>)>> {
+        with_pool!(self, pool => {
+            let row = sqlx::query("SELECT reason, tripped_at FROM risk_state WHERE id = 1 AND kill_switch = 1")
+                .fetch_optional(pool)
+                .await?;
+            
+            Ok(row.map(|row| (row.get("reason"), row.get("tripped_at"))))
+        })
+    }
+    
+    pub async fn set_kill_switch(&self, reason: &str) -> Result<()> {
+        with_pool!(self, pool => {
+            sqlx::query(
+                r#"
+                INSERT INTO risk_state (id, kill_switch, reason, tripped_at)
+                VALUES (1, 1, $1, $2)
+                ON CONFLICT(id) DO UPDATE SET kill_switch = 1, reason = excluded.reason, tripped_at = excluded.tripped_at
+                "#,
+            )
+            .bind(reason)
+            .bind(Utc::now())
+            .execute(pool)
+            .await?;
+        });
+        
+        Ok(())
+    }
+    
+    pub async fn clear_kill_switch(&self) -> Result<()> {
+        with_pool!(self, pool => {
+            sqlx::query(
+                r#"
+                INSERT INTO risk_state (id, kill_switch, reset_at)
+                VALUES (1, 0, $1)
+                ON CONFLICT(id) DO UPDATE SET kill_switch = 0, reason = NULL, tripped_at = NULL, reset_at = excluded.reset_at
+                "#,
+            )
+            .bind(Utc::now())
+            .execute(pool)
+            .await?;
+        });
+        
+        Ok(())
+    }
+    
+    pub async fn get_risk_reset_at(&self) -> Result<Option<DateTime<Utc>>> {
+        with_pool!(self, pool => {
+            let row = sqlx::query("SELECT reset_at FROM risk_state WHERE id = 1")
+                .fetch_optional(pool)
+                .await?;
+            
+            Ok(row.and_then(|row| row.get("reset_at")))
+        })
+    }
+    
+    pub async fn save_price_snapshots(&self, quotes: &[PriceQuote]) -> Result<()> {
+        with_pool!(self, pool => {
+            let mut tx = pool.begin().await?;
+            
+            for quote in quotes {
+                sqlx::query(
+                    r#"
+                    INSERT INTO price_snapshots (
+                        timestamp, dex_name, token_pair, block_number, price, liquidity, latency_ms
+                    )
+                    VALUES ($1, $2, $3, $4, $5, $6, $7)
+                    "#,
+                )
+                .bind(quote.timestamp)
+                .bind(&quote.dex_name)
+                .bind(&quote.token_pair)
+                .bind(quote.block_number.map(|block| block as i64))
+                .bind(quote.price.to_string())
+                .bind(quote.liquidity.to_string())
+                .bind(quote.latency_ms as i64)
+                .execute(&mut *tx)
+                .await?;
+            }
+            
+            tx.commit().await?;
+        });
+        
+        Ok(())
+    }
+    
+    pub async fn get_price_series(
+        &self,
+        token_pair: &str,
+        dex_name: Option<&str>,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Result<Vec<PriceSnapshot>> {
+        with_pool!(self, pool => {
+            let rows = sqlx::query(
+                r#"
+                SELECT * FROM price_snapshots
+                WHERE token_pair = $1 AND ($2 IS NULL OR dex_name = $2) AND timestamp >= $3 AND timestamp <= $4
+                ORDER BY timestamp ASC, id ASC
+                "#,
+            )
+            .bind(token_pair)
+            .bind(dex_name)
+            .bind(from)
+            .bind(to)
+            .fetch_all(pool)
+            .await?;
+            
+            rows.iter().map(|row| -> Result<PriceSnapshot> { Ok(price_snapshot_from_row!(row)) }).collect()
+        })
+    }
+    
+    // Streams stored quotes oldest first; unset filters match everything
+    pub async fn for_each_price_snapshot<F>(
+        &self,
+        token_pair: Option<&str>,
+        dex_name: Option<&str>,
+        from: Option<DateTime<Utc>>,
+        to: Option<DateTime<Utc>>,
+        mut sink: F,
+    ) -> Result<()>
+    where
+        F: FnMut(PriceSnapshot) -> Result<()>,
+    {
+        with_pool!(self, pool => {
+            let mut rows = sqlx::query(
+                r#"
+                SELECT * FROM price_snapshots
+                WHERE ($1 IS NULL OR token_pair = $1) AND ($2 IS NULL OR dex_name = $2)
+                  AND ($3 IS NULL OR timestamp >= $3) AND ($4 IS NULL OR timestamp <= $4)
+                ORDER BY timestamp ASC, id ASC
+                "#,
+            )
+            .bind(token_pair)
+            .bind(dex_name)
+            .bind(from)
+            .bind(to)
+            .fetch(pool);
+            
+            while let Some(row) = rows.try_next().await? {
+                sink(price_snapshot_from_row!(row))?;
+            }
+        });
+        
+        Ok(())
+    }
+    
+    pub async fn get_price_snapshots_in_blocks(&self, from_block: u64, to_block: u64) -> Result<Vec<PriceSnapshot>> {
+        with_pool!(self, pool => {
+            let rows = sqlx::query(
+                r#"
+                SELECT * FROM price_snapshots
+                WHERE block_number >= $1 AND block_number <= $2
+                ORDER BY block_number ASC, id ASC
+                "#,
+            )
+            .bind(from_block as i64)
+            .bind(to_block as i64)
+            .fetch_all(pool)
+            .await?;
+            
+            rows.iter().map(|row| -> Result<PriceSnapshot> { Ok(price_snapshot_from_row!(row)) }).collect()
+        })
+    }
+    
+    pub async fn prune_price_snapshots(&self, before: DateTime<Utc>) -> Result<u64> {
+        with_pool!(self, pool => {
+            let result = sqlx::query("DELETE FROM price_snapshots WHERE timestamp < $1")
+                .bind(before)
+                .execute(pool)
+                .await?;
+            
+            Ok(result.rows_affected())
+        })
+    }
+    
+    // Timestamp of the `max_rows`-th newest opportunity; older rows are over the limit
+    pub async fn opportunity_row_cutoff(&self, max_rows: u64) -> Result<Option<DateTime<Utc>>> {
+        with_pool!(self, pool => {
+            let row = sqlx::query("SELECT timestamp FROM arbitrage_opportunities ORDER BY timestamp DESC LIMIT 1 OFFSET $1")
+                .bind(max_rows.saturating_sub(1) as i64)
+                .fetch_optional(pool)
+                .await?;
+            
+            Ok(row.map(|row| row.get("timestamp")))
+        })
+    }
+    
+    // Deletes opportunities older than `before` that were never executed,
+    // optionally rolling them into opportunity_hourly first. Both happen in
+    // one transaction so a failure neither loses nor double-counts rows.
+    pub async fn prune_opportunities(&self, before: DateTime<Utc>, downsample: bool) -> Result<u64> {
+        const PRUNABLE: &str = "timestamp < $1 AND id NOT IN (SELECT opportunity_id FROM executions)";
+        let select = format!(
+            "SELECT timestamp, token_pair, buy_dex, sell_dex, profit_usd FROM arbitrage_opportunities WHERE {}",
+            PRUNABLE
+        );
+        let delete = format!("DELETE FROM arbitrage_opportunities WHERE {}", PRUNABLE);
+        
+        with_pool!(self, pool => {
+            let mut tx = pool.begin().await?;
+            
+            if downsample {
+                let mut buckets: BTreeMap<(DateTime<Utc>, String, String, String), HourlyBucket> = BTreeMap::new();
+                {
+                    let mut rows = sqlx::query(&select).bind(before).fetch(&mut *tx);
+                    
+                    while let Some(row) = rows.try_next().await? {
+                        let timestamp: DateTime<Utc> = row.get("timestamp");
+                        let hour = timestamp.duration_trunc(chrono::Duration::hours(1))?;
+                        let profit = parse_decimal(row.get("profit_usd"))?;
+                        buckets
+                            .entry((hour, row.get("token_pair"), row.get("buy_dex"), row.get("sell_dex")))
+                            .or_default()
+                            .add(1, profit, profit);
+                    }
+                }
+                
+                // Profits are TEXT, so existing buckets are merged here rather than in SQL
+                for ((hour, token_pair, buy_dex, sell_dex), mut bucket) in buckets {
+                    let existing = sqlx::query(
+                        r#"
+                        SELECT opportunities, total_profit_usd, max_profit_usd FROM opportunity_hourly
+                        WHERE hour = $1 AND token_pair = $2 AND buy_dex = $3 AND sell_dex = $4
+                        "#,
+                    )
+                    .bind(hour)
+                    .bind(&token_pair)
+                    .bind(&buy_dex)
+                    .bind(&sell_dex)
+                    .fetch_optional(&mut *tx)
+                    .await?;
+                    
+                    if let Some(row) = existing {
+                        bucket.add(
+                            row.get("opportunities"),
+                            parse_decimal(row.get("total_profit_usd"))?,
+                            parse_decimal(row.get("max_profit_usd"))?,
+                        );
+                    }
+                    
+                    sqlx::query(
+                        r#"
+                        INSERT INTO opportunity_hourly (hour, token_pair, buy_dex, sell_dex, opportunities, total_profit_usd, max_profit_usd)
+                        VALUES ($1, $2, $3, $4, $5, $6, $7)
+                        ON CONFLICT(hour, token_pair, buy_dex, sell_dex) DO UPDATE SET
+                            opportunities = excluded.opportunities,
+                            total_profit_usd = excluded.total_profit_usd,
+                            max_profit_usd = excluded.max_profit_usd
+                        "#,
+                    )
+                    .bind(hour)
+                    .bind(&token_pair)
+                    .bind(&buy_dex)
+                    .bind(&sell_dex)
+                    .bind(bucket.opportunities)
+                    .bind(bucket.total_profit_usd.to_string())
+                    .bind(bucket.max_profit_usd.unwrap_or_default().to_string())
+                    .execute(&mut *tx)
+                    .await?;
+                }
+            }
+            
+            let result = sqlx::query(&delete)
+                .bind(before)
+                .execute(&mut *tx)
+                .await?;
+            tx.commit().await?;
+            
+            Ok(result.rows_affected())
+        })
+    }
+    
+    // Returns space freed by pruning to the OS. On SQLite the WAL is
+    // checkpointed and truncated first so VACUUM sees every page.
+    pub async fn vacuum(&self) -> Result<()> {
+        match &self.pool {
+            DbPool::Sqlite(pool) => {
+                pool.execute("PRAGMA wal_checkpoint(TRUNCATE)").await?;
+                pool.execute("VACUUM").await?;
+            }
+            DbPool::Postgres(pool) => {
+                pool.execute("VACUUM ANALYZE arbitrage_opportunities").await?;
+            }
+        }
+        
+        Ok(())
+    }
+    
+    // Readiness probe: proves the database still accepts writes
+    pub async fn write_heartbeat(&self) -> Result<()> {
+        with_pool!(self, pool => {
+            sqlx::query(
+                r#"
+                INSERT INTO health_heartbeat (id, checked_at)
+                VALUES (1, $1)
+                ON CONFLICT(id) DO UPDATE SET checked_at = excluded.checked_at
+                "#,
+            )
+            .bind(Utc::now())
+            .execute(pool)
+            .await?;
+        });
+        
+        Ok(())
+    }
+    
+    pub async fn save_evaluated_spread(&self, spread: &ArbitrageOpportunity, rejection: Option<RejectionReason>) -> Result<i64> {
+        with_pool!(self, pool => {
+            let rows = sqlx::query(
+                r#"
+                INSERT INTO evaluated_spreads (
+                    timestamp, token_pair, buy_dex, sell_dex, buy_price, sell_price,
+                    price_difference, profit_usd, profit_percentage, trade_amount, gas_cost_usd,
+                    accepted, rejection_reason
+                )
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13)
+                RETURNING id
+                "#,
+            )
+            .bind(spread.timestamp)
+            .bind(&spread.token_pair)
+            .bind(&spread.buy_dex)
+            .bind(&spread.sell_dex)
+            .bind(spread.buy_price.to_string())
+            .bind(spread.sell_price.to_string())
+            .bind(spread.price_difference.to_string())
+            .bind(spread.profit_usd.to_string())
+            .bind(spread.profit_percentage.to_string())
+            .bind(spread.trade_amount.to_string())
+            .bind(spread.gas_cost_usd.to_string())
+            .bind(rejection.is_none())
+            .bind(rejection.map(|reason| reason.as_str()))
+            .fetch_all(pool)
+            .await?;
+            
+            Ok(rows[0].get("id"))
+        })
+    }
+    
+    pub async fn insert_lifecycle(&self, lifecycle: &OpportunityLifecycle) -> Result<i64> {
+        with_pool!(self, pool => {
+            let rows = sqlx::query(
+                r#"
+                INSERT INTO opportunity_lifecycles (
+                    token_pair, buy_dex, sell_dex, first_seen, last_seen, first_block, last_block,
+                    observations, initial_profit_usd, peak_profit_usd, last_profit_usd, close_reason, closed_at
+                )
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13)
+                RETURNING id
+                "#,
+            )
+            .bind(&lifecycle.token_pair)
+            .bind(&lifecycle.buy_dex)
+            .bind(&lifecycle.sell_dex)
+            .bind(lifecycle.first_seen)
+            .bind(lifecycle.last_seen)
+            .bind(lifecycle.first_block)
+            .bind(lifecycle.last_block)
+            .bind(lifecycle.observations)
+            .bind(lifecycle.initial_profit_usd.to_string())
+            .bind(lifecycle.peak_profit_usd.to_string())
+            .bind(lifecycle.last_profit_usd.to_string())
+            .bind(&lifecycle.close_reason)
+            .bind(lifecycle.closed_at)
+            .fetch_all(pool)
+            .await?;
+            
+            Ok(rows[0].get("id"))
+        })
+    }
+    
+    pub async fn update_lifecycle(&self, lifecycle: &OpportunityLifecycle) -> Result<()> {
+        let id = lifecycle.id.ok_or_else(|| anyhow::anyhow!("Lifecycle has not been inserted"))?;
+        
+        with_pool!(self, pool => {
+            sqlx::query(
+                r#"
+                UPDATE opportunity_lifecycles
+                SET last_seen = $1, last_block = $2, observations = $3, peak_profit_usd = $4,
+                    last_profit_usd = $5, close_reason = $6, closed_at = $7
+                WHERE id = $8
+                "#,
+            )
+            .bind(lifecycle.last_seen)
+            .bind(lifecycle.last_block)
+            .bind(lifecycle.observations)
+            .bind(lifecycle.peak_profit_usd.to_string())
+            .bind(lifecycle.last_profit_usd.to_string())
+            .bind(&lifecycle.close_reason)
+            .bind(lifecycle.closed_at)
+            .bind(id)
+            .execute(pool)
+            .await?;
+        });
+        
+        Ok(())
+    }
+    
+    pub async fn close_open_lifecycles(&self, reason: &str) -> Result<u64> {
+        with_pool!(self, pool => {
+            let result = sqlx::query("UPDATE opportunity_lifecycles SET close_reason = $1, closed_at = $2 WHERE closed_at IS NULL")
+                .bind(reason)
+                .bind(Utc::now())
+                .execute(pool)
+                .await?;
+            
+            Ok(result.rows_affected())
+        })
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use std::str::FromStr;
+    
+    // A fresh SQLite file per test; reopening the URL sees the same data
+    pub(crate) fn temp_database_url(name: &str) -> String {
+        let path = std::env::temp_dir().join(format!("arbitrage-{}-{}.db", name, std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        format!("sqlite:{}?mode=rwc", path.display())
+    }
+    
+    pub(crate) async fn temp_database(name: &str) -> Database {
+        let database = Database::new(&temp_database_url(name)).await.unwrap();
+        database.migrate().await.unwrap();
+        database
+    }
+    
+    // Every backend a test should run against: SQLite always, and PostgreSQL
+    // when DATABASE_URL names a scratch database. Each test migrates its own
+    // schema there, so tests can share the database and run in parallel.
+    async fn test_databases(name: &str) -> Vec<Database> {
+        let mut databases = vec![temp_database(name).await];
+        
+        let Ok(url) = std::env::var("DATABASE_URL") else {
+            return databases;
+        };
+        if !url.starts_with("postgres://") && !url.starts_with("postgresql://") {
+            return databases;
+        }
+        
+        let schema = format!("test_{}", name.replace('-', "_"));
+        let admin = PgPool::connect(&url).await.unwrap();
+        admin
+            .execute(format!("DROP SCHEMA IF EXISTS {schema} CASCADE; CREATE SCHEMA {schema}").as_str())
+            .await
+            .unwrap();
+        admin.close().await;
+        
+        let separator = if url.contains('?') { '&' } else { '?' };
+        let database = Database::new(&format!("{url}{separator}options=-c%20search_path%3D{schema}")).await.unwrap();
+        database.migrate().await.unwrap();
+        databases.push(database);
+        databases
+    }
+    
+    pub(crate) fn opportunity(token_pair: &str, buy_dex: &str, profit_usd: &str, timestamp: DateTime<Utc>) -> ArbitrageOpportunity {
+        ArbitrageOpportunity {
+            id: None,
+            timestamp,
+            token_pair: token_pair.to_string(),
+            buy_dex: buy_dex.to_string(),
+            sell_dex: "SushiSwap".to_string(),
+            buy_price: Decimal::ONE,
+            sell_price: Decimal::ONE,
+            price_difference: Decimal::ZERO,
+            profit_usd: Decimal::from_str(profit_usd).unwrap(),
+            profit_percentage: Decimal::ZERO,
+            trade_amount: Decimal::ONE_THOUSAND,
+            gas_cost_usd: Decimal::ZERO,
+        }
+    }
+    
+    // (accepted, rejection_reason) of every stored spread, oldest first
+    pub(crate) async fn evaluated_spread_rows(database: &Database) -> Vec<(bool, Option<String>)> {
+        with_pool!(database, pool => {
+            sqlx::query("SELECT accepted, rejection_reason FROM evaluated_spreads ORDER BY id")
+                .fetch_all(pool)
+                .await
+                .unwrap()
+                .into_iter()
+                .map(|row| (row.get("accepted"), row.get("rejection_reason")))
+                .collect()
+        })
+    }
+    
+    pub(crate) async fn lifecycle_rows(database: &Database) -> Vec<OpportunityLifecycle> {
+        with_pool!(database, pool => {
+            sqlx::query("SELECT * FROM opportunity_lifecycles ORDER BY id")
+                .fetch_all(pool)
+                .await
+                .unwrap()
+                .into_iter()
+                .map(|row| OpportunityLifecycle {
+                    id: Some(row.get("id")),
+                    token_pair: row.get("token_pair"),
+                    buy_dex: row.get("buy_dex"),
+                    sell_dex: row.get("sell_dex"),
+                    first_seen: row.get("first_seen"),
+                    last_seen: row.get("last_seen"),
+                    first_block: row.get("first_block"),
+                    last_block: row.get("last_block"),
+                    observations: row.get("observations"),
+                    initial_profit_usd: row.get::<String, _>("initial_profit_usd").parse().unwrap(),
+                    peak_profit_usd: row.get::<String, _>("peak_profit_usd").parse().unwrap(),
+                    last_profit_usd: row.get::<String, _>("last_profit_usd").parse().unwrap(),
+                    close_reason: row.get("close_reason"),
+                    closed_at: row.get("closed_at"),
+                })
+                .collect()
+        })
+    }
+    
+    fn execution(opportunity_id: i64, timestamp: DateTime<Utc>) -> Execution {
+        Execution {
+            id: None,
+            opportunity_id,
+            timestamp,
+            tx_hashes: vec!["0x01".to_string()],
+            block_number: Some(1),
+            status: "success".to_string(),
+            quote_token: "USDC".to_string(),
+            quote_spent: Decimal::ZERO,
+            quote_received: Decimal::ZERO,
+            base_token: "WETH".to_string(),
+            base_residual: Decimal::ZERO,
+            swap_count: 2,
+            gas_used: 0,
+            gas_cost_native: Decimal::ZERO,
+            gas_cost_usd: Decimal::ZERO,
+            realised_pnl_usd: Decimal::ZERO,
+        }
+    }
+    
+    #[tokio::test]
+    async fn opportunity_query_filters_orders_and_pages() {
+        for database in test_databases("opportunity-query").await {
+            let start = Utc::now() - chrono::Duration::hours(3);
+            
+            let old = database.save_opportunity(&opportunity("WETH/USDC", "QuickSwap", "2.5", start)).await.unwrap();
+            let big = database
+                .save_opportunity(&opportunity("WETH/USDC", "QuickSwap", "12", start + chrono::Duration::hours(1)))
+                .await
+                .unwrap();
+            let other_dex = database
+                .save_opportunity(&opportunity("WETH/USDC", "Uniswap", "9", start + chrono::Duration::hours(2)))
+                .await
+                .unwrap();
+            database
+                .save_opportunity(&opportunity("WMATIC/USDC", "QuickSwap", "30", start + chrono::Duration::hours(2)))
+                .await
+                .unwrap();
+            
+            database.save_execution(&execution(big, Utc::now())).await.unwrap();
+            
+            let ids = |opportunities: Vec<ArbitrageOpportunity>| -> Vec<i64> {
+                opportunities.into_iter().map(|opportunity| opportunity.id.unwrap()).collect()
+            };
+            let weth = OpportunityQuery::new().token_pair("WETH/USDC");
+            
+            assert_eq!(ids(database.query_opportunities(&weth).await.unwrap()), vec![other_dex, big, old]);
+            assert_eq!(ids(database.query_opportunities(&weth.clone().buy_dex("QuickSwap")).await.unwrap()), vec![big, old]);
+            assert_eq!(
+                ids(database.query_opportunities(&weth.clone().since(start + chrono::Duration::minutes(30))).await.unwrap()),
+                vec![other_dex, big]
+            );
+            // Profits compare numerically even though they are stored as text ("12" < "9" as strings)
+            assert_eq!(
+                ids(database.query_opportunities(&weth.clone().min_profit_usd(Decimal::from(9))).await.unwrap()),
+                vec![other_dex, big]
+            );
+            assert_eq!(
+                ids(database.query_opportunities(&weth.clone().order(OpportunityOrder::MostProfitable)).await.unwrap()),
+                vec![big, other_dex, old]
+            );
+            assert_eq!(ids(database.query_opportunities(&weth.clone().executed(true)).await.unwrap()), vec![big]);
+            assert_eq!(ids(database.query_opportunities(&weth.clone().executed(false)).await.unwrap()), vec![other_dex, old]);
+            assert_eq!(
+                ids(database.query_opportunities(&weth.clone().order(OpportunityOrder::Oldest).limit(1).offset(1)).await.unwrap()),
+                vec![big]
+            );
+        }
+    }
+    
+    #[tokio::test]
+    async fn pre_versioning_databases_upgrade_to_the_latest_schema() {
+        let database = Database::new(&temp_database_url("pre-versioning")).await.unwrap();
+        let DbPool::Sqlite(pool) = &database.pool else {
+            unreachable!("temp databases are SQLite");
+        };
+        
+        // The schema written by releases before schema_version existed
+        pool.execute(
             r#"
             CREATE TABLE IF NOT EXISTS arbitrage_opportunities (
                 id INTEGER PRIMARY KEY AUTOINCREMENT,
@@ -46,82 +1459,300 @@ impl Database {
                 profit_percentage DECIMAL(10, 4) NOT NULL,
                 trade_amount DECIMAL(20, 8) NOT NULL,
                 gas_cost_usd DECIMAL(20, 8) NOT NULL
-            )
-            "#,
-        )
-        .execute(&self.pool)
-        .await?;
-        
-        sqlx::query(
-            r#"
+            );
             CREATE INDEX IF NOT EXISTS idx_timestamp ON arbitrage_opportunities(timestamp);
             CREATE INDEX IF NOT EXISTS idx_token_pair ON arbitrage_opportunities(token_pair);
             CREATE INDEX IF NOT EXISTS idx_profit_usd ON arbitrage_opportunities(profit_usd);
             "#,
         )
-        .execute(&self.pool)
-        .await?;
+        .await
+        .unwrap();
+        let seen_at = DateTime::parse_from_rfc3339("2026-01-05T08:30:00Z").unwrap().with_timezone(&Utc);
+        sqlx::query("INSERT INTO arbitrage_opportunities (timestamp, token_pair, buy_dex, sell_dex, buy_price, sell_price, price_difference, profit_usd, profit_percentage, trade_amount, gas_cost_usd) VALUES (?, 'WETH/USDC', 'QuickSwap', 'SushiSwap', ?, ?, ?, ?, ?, ?, ?)")
+            .bind(seen_at)
+            .bind(1834.5)
+            .bind(1840.0)
+            .bind(5.5)
+            .bind(2.75)
+            .bind(0.2998)
+            .bind(1000.0)
+            .bind(0.02)
+            .execute(pool)
+            .await
+            .unwrap();
         
-        Ok(())
+        assert!(database.migration_status().await.unwrap().iter().all(|status| status.applied_at.is_none()));
+        assert_eq!(database.migrate().await.unwrap(), MIGRATIONS.len());
+        
+        let statuses = database.migration_status().await.unwrap();
+        assert_eq!(statuses.len(), MIGRATIONS.len());
+        assert!(statuses.iter().all(|status| status.applied_at.is_some()));
+        assert_eq!(database.migrate().await.unwrap(), 0);
+        
+        // The old row survives with its values, and the new tables are usable
+        let kept = database.get_recent_opportunities(1).await.unwrap().remove(0);
+        assert_eq!(kept.timestamp, seen_at);
+        assert_eq!(kept.buy_price, Decimal::from_str("1834.5").unwrap());
+        assert_eq!(kept.profit_usd, Decimal::from_str("2.75").unwrap());
+        assert_eq!(kept.profit_percentage, Decimal::from_str("0.2998").unwrap());
+        database.save_execution(&execution(kept.id.unwrap(), Utc::now())).await.unwrap();
+        assert_eq!(database.get_execution_report().await.unwrap()[0].estimated_profit_usd, kept.profit_usd);
+        database.set_kill_switch("upgrade test").await.unwrap();
+        assert!(database.get_kill_switch().await.unwrap().is_some());
     }
     
-    pub async fn save_opportunity(&self, opportunity: &ArbitrageOpportunity) -> Result<i64> {
-        let result = sqlx::query(
-            r#"
-            INSERT INTO arbitrage_opportunities (
-                timestamp, token_pair, buy_dex, sell_dex, buy_price, sell_price,
-                price_difference, profit_usd, profit_percentage, trade_amount, gas_cost_usd
-            )
-            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
-            "#,
-        )
-        .bind(&opportunity.timestamp)
-        .bind(&opportunity.token_pair)
-        .bind(&opportunity.buy_dex)
-        .bind(&opportunity.sell_dex)
-        .bind(&opportunity.buy_price)
-        .bind(&opportunity.sell_price)
-        .bind(&opportunity.price_difference)
-        .bind(&opportunity.profit_usd)
-        .bind(&opportunity.profit_percentage)
-        .bind(&opportunity.trade_amount)
-        .bind(&opportunity.gas_cost_usd)
-        .execute(&self.pool)
-        .await?;
-        
-        Ok(result.last_insert_rowid())
+    #[tokio::test]
+    async fn migrations_apply_on_every_backend() {
+        for database in test_databases("migrations").await {
+            let statuses = database.migration_status().await.unwrap();
+            assert_eq!(statuses.len(), MIGRATIONS.len());
+            assert!(statuses.iter().all(|status| status.applied_at.is_some()));
+            assert_eq!(database.migrate().await.unwrap(), 0);
+            
+            // The tables each backend created accept the same writes
+            let id = database.save_opportunity(&opportunity("WETH/USDC", "QuickSwap", "2.5", Utc::now())).await.unwrap();
+            database.save_execution(&execution(id, Utc::now())).await.unwrap();
+            assert_eq!(database.get_execution_report().await.unwrap()[0].executions, 1);
+        }
     }
     
-    pub async fn get_recent_opportunities(&self, limit: i64) -> Result<Vec<ArbitrageOpportunity>> {
-        let rows = sqlx::query(
-            r#"
-            SELECT * FROM arbitrage_opportunities
-            ORDER BY timestamp DESC
-            LIMIT ?
-            "#,
-        )
-        .bind(limit)
-        .fetch_all(&self.pool)
-        .await?;
+    #[tokio::test]
+    async fn opportunity_decimals_round_trip_exactly() {
+        for database in test_databases("decimal-round-trip").await {
+            // More significant digits than an f64 holds, and far below DECIMAL(20, 8) precision
+            let opportunity = ArbitrageOpportunity {
+                id: None,
+                timestamp: Utc::now(),
+                token_pair: "WETH/USDC".to_string(),
+                buy_dex: "QuickSwap".to_string(),
+                sell_dex: "SushiSwap".to_string(),
+                buy_price: Decimal::from_str("1834.123456789012345678").unwrap(),
+                sell_price: Decimal::from_str("1834.123456789012345679").unwrap(),
+                price_difference: Decimal::from_str("0.000000000000000001").unwrap(),
+                profit_usd: Decimal::from_str("0.000000012345678901").unwrap(),
+                profit_percentage: Decimal::from_str("0.0000000000000000545").unwrap(),
+                trade_amount: Decimal::from_str("1000.10").unwrap(),
+                gas_cost_usd: Decimal::from_str("0.01234567890123456789").unwrap(),
+            };
+            
+            let id = database.save_opportunity(&opportunity).await.unwrap();
+            let loaded = database.get_recent_opportunities(1).await.unwrap().remove(0);
+            
+            assert_eq!(loaded.id, Some(id));
+            assert_eq!(loaded.buy_price, opportunity.buy_price);
+            assert_eq!(loaded.sell_price, opportunity.sell_price);
+            assert_eq!(loaded.price_difference, opportunity.price_difference);
+            assert_eq!(loaded.profit_usd, opportunity.profit_usd);
+            assert_eq!(loaded.profit_percentage, opportunity.profit_percentage);
+            assert_eq!(loaded.gas_cost_usd, opportunity.gas_cost_usd);
+            // Scale is preserved too, not just the value
+            assert_eq!(loaded.trade_amount.to_string(), "1000.10");
+        }
+    }
+    
+    #[tokio::test]
+    async fn decimal_migration_keeps_executed_opportunities() {
+        let database = Database::new(&temp_database_url("decimal-migration")).await.unwrap();
+        let DbPool::Sqlite(pool) = &database.pool else {
+            unreachable!("temp databases are SQLite");
+        };
         
-        let mut opportunities = Vec::new();
-        for row in rows {
-            opportunities.push(ArbitrageOpportunity {
-                id: Some(row.get("id")),
-                timestamp: row.get("timestamp"),
-                token_pair: row.get("token_pair"),
-                buy_dex: row.get("buy_dex"),
-                sell_dex: row.get("sell_dex"),
-                buy_price: row.get("buy_price"),
-                sell_price: row.get("sell_price"),
-                price_difference: row.get("price_difference"),
-                profit_usd: row.get("profit_usd"),
-                profit_percentage: row.get("profit_percentage"),
-                trade_amount: row.get("trade_amount"),
-                gas_cost_usd: row.get("gas_cost_usd"),
-            });
+        // A database left at version 7, with float decimals and an execution
+        // referencing its opportunity
+        pool.execute("CREATE TABLE schema_version (version INTEGER PRIMARY KEY, name TEXT NOT NULL, applied_at DATETIME NOT NULL)")
+            .await
+            .unwrap();
+        for migration in MIGRATIONS.iter().filter(|migration| migration.version <= 7) {
+            pool.execute(migration.sqlite).await.unwrap();
+            sqlx::query("INSERT INTO schema_version (version, name, applied_at) VALUES ($1, $2, $3)")
+                .bind(migration.version)
+                .bind(migration.name)
+                .bind(Utc::now())
+                .execute(pool)
+                .await
+                .unwrap();
         }
+        sqlx::query("INSERT INTO arbitrage_opportunities (timestamp, token_pair, buy_dex, sell_dex, buy_price, sell_price, price_difference, profit_usd, profit_percentage, trade_amount, gas_cost_usd) VALUES ($1, 'WETH/USDC', 'QuickSwap', 'SushiSwap', 1834.5, 1836.25, 1.75, 0.000015, 0.05, 1000, 0.02)")
+            .bind(Utc::now())
+            .execute(pool)
+            .await
+            .unwrap();
+        sqlx::query("INSERT INTO executions (opportunity_id, timestamp, tx_hashes, status, quote_token, quote_spent, quote_received, base_token, base_residual, swap_count, gas_used, gas_cost_native, gas_cost_usd, realised_pnl_usd) VALUES (1, $1, '0x01', 'success', 'USDC', '1000', '1000.5', 'WETH', '0', 2, 300000, '0.01', '0.01', '0.49')")
+            .bind(Utc::now())
+            .execute(pool)
+            .await
+            .unwrap();
         
-        Ok(opportunities)
+        assert_eq!(database.migrate().await.unwrap(), MIGRATIONS.len() - 7);
+        assert!(sqlx::query("PRAGMA foreign_key_check").fetch_all(pool).await.unwrap().is_empty());
+        
+        let converted = database.get_recent_opportunities(1).await.unwrap().remove(0);
+        assert_eq!(converted.buy_price, Decimal::from_str("1834.5").unwrap());
+        assert_eq!(converted.profit_usd, Decimal::from_str("0.000015").unwrap());
+        assert_eq!(converted.trade_amount, Decimal::ONE_THOUSAND);
+        
+        let report = database.get_execution_report().await.unwrap();
+        assert_eq!(report.len(), 1);
+        assert_eq!(report[0].estimated_profit_usd, converted.profit_usd);
+    }
+    
+    #[tokio::test]
+    async fn execution_report_groups_routes_by_token_pair() {
+        for database in test_databases("execution-report").await {
+            for (pair, profit) in [("WETH/USDC", "1.5"), ("WBTC/USDC", "4"), ("WETH/USDC", "2.5")] {
+                let id = database.save_opportunity(&opportunity(pair, "QuickSwap", profit, Utc::now())).await.unwrap();
+                database.save_execution(&execution(id, Utc::now())).await.unwrap();
+            }
+            
+            let report = database.get_execution_report().await.unwrap();
+            let pairs: Vec<(&str, i64, Decimal)> = report
+                .iter()
+                .map(|route| (route.token_pair.as_str(), route.executions, route.estimated_profit_usd))
+                .collect();
+            assert_eq!(pairs, vec![("WBTC/USDC", 1, Decimal::from(4)), ("WETH/USDC", 2, Decimal::from(4))]);
+        }
+    }
+    
+    fn price_quote(dex_name: &str, token_pair: &str, price: &str, block_number: Option<u64>, timestamp: DateTime<Utc>) -> PriceQuote {
+        PriceQuote {
+            dex_name: dex_name.to_string(),
+            token_pair: token_pair.to_string(),
+            price: Decimal::from_str(price).unwrap(),
+            liquidity: Decimal::from_str("250000.5").unwrap(),
+            timestamp,
+            block_number,
+            latency_ms: 42,
+        }
+    }
+    
+    #[tokio::test]
+    async fn price_snapshots_round_trip_filter_and_prune() {
+        for database in test_databases("price-snapshots").await {
+            let noon = DateTime::parse_from_rfc3339("2026-10-18T12:00:00Z").unwrap().with_timezone(&Utc);
+            let hour = chrono::Duration::hours(1);
+            database
+                .save_price_snapshots(&[
+                    price_quote("QuickSwap", "WETH/USDC", "1834.123456789012345678", Some(58_000_100), noon - hour * 2),
+                    price_quote("SushiSwap", "WETH/USDC", "1835.5", Some(58_000_101), noon - hour),
+                    price_quote("QuickSwap", "WBTC/USDC", "34000", Some(58_000_101), noon - hour),
+                    price_quote("QuickSwap", "WETH/USDC", "1836", None, noon),
+                ])
+                .await
+                .unwrap();
+            
+            let series = database.get_price_series("WETH/USDC", None, noon - hour * 3, noon).await.unwrap();
+            assert_eq!(series.len(), 3);
+            let first = &series[0];
+            assert_eq!(first.price, Decimal::from_str("1834.123456789012345678").unwrap());
+            assert_eq!(first.liquidity, Decimal::from_str("250000.5").unwrap());
+            assert_eq!((first.block_number, first.latency_ms, first.timestamp), (Some(58_000_100), 42, noon - hour * 2));
+            assert_eq!(series[2].block_number, None);
+            
+            let quickswap = database.get_price_series("WETH/USDC", Some("QuickSwap"), noon - hour * 3, noon).await.unwrap();
+            assert_eq!(quickswap.iter().map(|s| s.price.to_string()).collect::<Vec<_>>(), vec!["1834.123456789012345678", "1836"]);
+            let last_hour = database.get_price_series("WETH/USDC", None, noon - hour, noon - hour).await.unwrap();
+            assert_eq!(last_hour.len(), 1);
+            assert_eq!(last_hour[0].dex_name, "SushiSwap");
+            
+            let blocks = database.get_price_snapshots_in_blocks(58_000_101, 58_000_200).await.unwrap();
+            assert_eq!(blocks.iter().map(|s| s.token_pair.as_str()).collect::<Vec<_>>(), vec!["WETH/USDC", "WBTC/USDC"]);
+            
+            // Retention drops only what is strictly older than the cutoff
+            assert_eq!(database.prune_price_snapshots(noon - hour).await.unwrap(), 1);
+            let remaining = database.get_price_series("WETH/USDC", None, noon - hour * 3, noon).await.unwrap();
+            assert_eq!(remaining.iter().map(|s| s.timestamp).collect::<Vec<_>>(), vec![noon - hour, noon]);
+        }
+    }
+    
+    #[tokio::test]
+    async fn evaluated_spreads_keep_their_rejection_reason() {
+        for database in test_databases("evaluated-spreads").await {
+            let spread = opportunity("WETH/USDC", "QuickSwap", "0.25", Utc::now());
+            database.save_evaluated_spread(&spread, Some(RejectionReason::StaleQuote)).await.unwrap();
+            database.save_evaluated_spread(&spread, None).await.unwrap();
+            database.save_evaluated_spread(&spread, Some(RejectionReason::BelowPercentageThreshold)).await.unwrap();
+            
+            assert_eq!(
+                evaluated_spread_rows(&database).await,
+                vec![
+                    (false, Some("stale_quote".to_string())),
+                    (true, None),
+                    (false, Some("below_percentage_threshold".to_string())),
+                ]
+            );
+        }
     }
-}
\ No newline at end of file
+    
+    #[tokio::test]
+    async fn opportunities_are_counted_by_utc_hour() {
+        for database in test_databases("hourly-counts").await {
+            let day = DateTime::parse_from_rfc3339("2026-10-18T00:00:00Z").unwrap().with_timezone(&Utc);
+            
+            for offset in [chrono::Duration::minutes(5), chrono::Duration::minutes(50), chrono::Duration::hours(13)] {
+                database.save_opportunity(&opportunity("WETH/USDC", "QuickSwap", "1", day + offset)).await.unwrap();
+            }
+            
+            let counts = database.count_opportunities_by_hour(day, day + chrono::Duration::days(1)).await.unwrap();
+            assert_eq!(counts, vec![(0, 2), (13, 1)]);
+        }
+    }
+    
+    #[tokio::test]
+    async fn pruning_rolls_opportunities_into_hourly_buckets() {
+        for database in test_databases("prune-downsample").await {
+            let hour = DateTime::parse_from_rfc3339("2026-10-01T10:00:00Z").unwrap().with_timezone(&Utc);
+            
+            let executed = database.save_opportunity(&opportunity("WETH/USDC", "QuickSwap", "50", hour)).await.unwrap();
+            for (minutes, profit) in [(5, "1.25"), (40, "3.5")] {
+                database
+                    .save_opportunity(&opportunity("WETH/USDC", "QuickSwap", profit, hour + chrono::Duration::minutes(minutes)))
+                    .await
+                    .unwrap();
+            }
+            let recent = database
+                .save_opportunity(&opportunity("WETH/USDC", "QuickSwap", "2", hour + chrono::Duration::days(2)))
+                .await
+                .unwrap();
+            database.save_execution(&execution(executed, hour)).await.unwrap();
+            
+            // Keeping 2 rows puts the cutoff at the second newest opportunity
+            assert_eq!(
+                database.opportunity_row_cutoff(2).await.unwrap(),
+                Some(hour + chrono::Duration::minutes(40))
+            );
+            
+            let cutoff = hour + chrono::Duration::days(1);
+            assert_eq!(database.prune_opportunities(cutoff, true).await.unwrap(), 2);
+            // A second pass over an already pruned hour merges into the existing bucket
+            database.save_opportunity(&opportunity("WETH/USDC", "QuickSwap", "0.25", hour + chrono::Duration::minutes(59))).await.unwrap();
+            assert_eq!(database.prune_opportunities(cutoff, true).await.unwrap(), 1);
+            
+            let remaining: Vec<i64> = database
+                .query_opportunities(&OpportunityQuery::new().order(OpportunityOrder::Oldest))
+                .await
+                .unwrap()
+                .into_iter()
+                .map(|opportunity| opportunity.id.unwrap())
+                .collect();
+            assert_eq!(remaining, vec![executed, recent]);
+            
+            let buckets: Vec<(DateTime<Utc>, i64, String, String)> = with_pool!(database, pool => {
+                sqlx::query("SELECT hour, opportunities, total_profit_usd, max_profit_usd FROM opportunity_hourly")
+                    .fetch_all(pool)
+                    .await
+                    .unwrap()
+                    .into_iter()
+                    .map(|row| (row.get("hour"), row.get("opportunities"), row.get("total_profit_usd"), row.get("max_profit_usd")))
+                    .collect()
+            });
+            assert_eq!(buckets, vec![(hour, 3, "5.00".to_string(), "3.5".to_string())]);
+        }
+    }
+    
+    #[test]
+    fn parse_decimal_accepts_converted_float_rows() {
+        assert_eq!(parse_decimal("1834.5").unwrap(), Decimal::from_str("1834.5").unwrap());
+        assert_eq!(parse_decimal("1.5e-05").unwrap(), Decimal::from_str("0.000015").unwrap());
+        assert!(parse_decimal("not a number").is_err());
+    }
+}
@@ -1,7 +1,7 @@
+use async_trait::async_trait;
 use ethers::{
-    providers::{Provider, Http},
-    types::{Address, U256},
-    contract::Contract,
+    providers::Middleware,
+    types::{BlockId, U256},
     abi::Abi,
 };
 use anyhow::Result;
@@ -11,9 +11,13 @@ use rust_decimal::Decimal;
 
 pub mod uniswap;
 pub mod quickswap;
+pub mod synthetic;
 
-use crate::config::{Config, DexConfig, TokenConfig};
+use crate::config::{Config, TokenConfig};
 use crate::errors::{ArbitrageError, Result as ArbitrageResult};
+use crate::health::HEALTH;
+use crate::metrics::METRICS;
+use crate::rpc::{self, RpcProvider};
 
 #[derive(Debug, Clone)]
 pub struct TokenPair {
@@ -28,20 +32,30 @@ pub struct PriceQuote {
     pub price: Decimal, // token1 per token0
     pub liquidity: Decimal,
     pub timestamp: chrono::DateTime<chrono::Utc>,
+    pub block_number: Option<u64>,
+    pub latency_ms: u64,
 }
 
+#[async_trait]
 pub trait DexPriceFetcher: Send + Sync {
-    async fn get_price(&self, token_pair: &TokenPair) -> ArbitrageResult<PriceQuote>;
+    async fn get_price(&self, token_pair: &TokenPair) -> ArbitrageResult<PriceQuote> {
+        self.get_price_at(token_pair, None).await
+    }
+    
+    // Quote as of a past block; needs an archive node for anything but recent blocks
+    async fn get_price_at(&self, token_pair: &TokenPair, block: Option<BlockId>) -> ArbitrageResult<PriceQuote>;
     fn get_name(&self) -> &str;
 }
 
 pub struct DexManager {
+    provider: Arc<RpcProvider>,
     fetchers: Vec<Box<dyn DexPriceFetcher>>,
+    chain_fetchers: usize,
 }
 
 impl DexManager {
     pub async fn new(config: Config) -> Result<Self> {
-        let provider = Arc::new(Provider::<Http>::try_from(&config.polygon.rpc_url)?);
+        let provider = rpc::connect(&config.polygon)?;
         let mut fetchers: Vec<Box<dyn DexPriceFetcher>> = Vec::new();
         
         if config.dexes.uniswap_v2.enabled {
@@ -60,16 +74,40 @@ impl DexManager {
             fetchers.push(Box::new(fetcher));
         }
         
-        Ok(Self { fetchers })
+        let chain_fetchers = fetchers.len();
+        
+        for synthetic in config.synthetic_dexes.into_iter().filter(|dex| dex.enabled) {
+            fetchers.push(Box::new(synthetic::SyntheticFetcher::new(synthetic)?));
+        }
+        
+        HEALTH.track_fetchers(fetchers.iter().map(|fetcher| fetcher.get_name()));
+        
+        Ok(Self { provider, fetchers, chain_fetchers })
     }
     
     pub async fn get_all_prices(&self, token_pair: &TokenPair) -> Vec<PriceQuote> {
         let mut prices = Vec::new();
         
+        // Quotes are taken at "latest"; tag them with the head block seen just before
+        let block_number = if self.chain_fetchers > 0 {
+            self.provider.get_block_number().await.ok().map(|block| block.as_u64())
+        } else {
+            None
+        };
+        
         for fetcher in &self.fetchers {
+            let started = std::time::Instant::now();
             match fetcher.get_price(token_pair).await {
-                Ok(quote) => prices.push(quote),
+                Ok(mut quote) => {
+                    METRICS.quotes.inc(&[fetcher.get_name(), "ok"]);
+                    HEALTH.record_quote(fetcher.get_name(), None);
+                    quote.latency_ms = started.elapsed().as_millis() as u64;
+                    quote.block_number = quote.block_number.or(block_number);
+                    prices.push(quote);
+                }
                 Err(e) => {
+                    METRICS.quotes.inc(&[fetcher.get_name(), "failed"]);
+                    HEALTH.record_quote(fetcher.get_name(), Some(e.to_string()));
                     tracing::warn!("Failed to get price from {}: {}", fetcher.get_name(), e);
                 }
             }
@@ -77,6 +115,34 @@ impl DexManager {
         
         prices
     }
+    
+    pub async fn get_all_prices_at(&self, token_pair: &TokenPair, block: u64) -> Vec<PriceQuote> {
+        let mut prices = Vec::new();
+        
+        for fetcher in &self.fetchers {
+            let started = std::time::Instant::now();
+            match fetcher.get_price_at(token_pair, Some(block.into())).await {
+                Ok(mut quote) => {
+                    quote.latency_ms = started.elapsed().as_millis() as u64;
+                    quote.block_number = Some(block);
+                    prices.push(quote);
+                }
+                Err(e) => {
+                    tracing::warn!("Failed to get price from {} at block {}: {}", fetcher.get_name(), block, e);
+                }
+            }
+        }
+        
+        prices
+    }
+    
+    pub async fn get_block_timestamp(&self, block: u64) -> ArbitrageResult<chrono::DateTime<chrono::Utc>> {
+        let block = self.provider.get_block(block).await?
+            .ok_or_else(|| ArbitrageError::PriceFetch(format!("Block {} not found", block)))?;
+        
+        chrono::DateTime::from_timestamp(block.timestamp.as_u64() as i64, 0)
+            .ok_or_else(|| ArbitrageError::PriceFetch("Invalid block timestamp".to_string()))
+    }
 }
 
 // Uniswap V2 Router ABI (simplified)
@@ -95,4 +161,75 @@ pub fn get_uniswap_v2_abi() -> Abi {
             "type": "function"
         }
     ]"#).unwrap()
-}
\ No newline at end of file
+}
+
+// Uniswap V2 Factory ABI, just enough to look up a pair's pool address
+pub fn get_uniswap_v2_factory_abi() -> Abi {
+    serde_json::from_str(r#"[
+        {
+            "inputs": [
+                {"internalType": "address", "name": "tokenA", "type": "address"},
+                {"internalType": "address", "name": "tokenB", "type": "address"}
+            ],
+            "name": "getPair",
+            "outputs": [{"internalType": "address", "name": "pair", "type": "address"}],
+            "stateMutability": "view",
+            "type": "function"
+        }
+    ]"#).unwrap()
+}
+
+// Minimal ERC-20 ABI for balance, allowance, approval and metadata calls
+pub fn get_erc20_abi() -> Abi {
+    serde_json::from_str(r#"[
+        {
+            "inputs": [{"internalType": "address", "name": "account", "type": "address"}],
+            "name": "balanceOf",
+            "outputs": [{"internalType": "uint256", "name": "", "type": "uint256"}],
+            "stateMutability": "view",
+            "type": "function"
+        },
+        {
+            "inputs": [
+                {"internalType": "address", "name": "owner", "type": "address"},
+                {"internalType": "address", "name": "spender", "type": "address"}
+            ],
+            "name": "allowance",
+            "outputs": [{"internalType": "uint256", "name": "", "type": "uint256"}],
+            "stateMutability": "view",
+            "type": "function"
+        },
+        {
+            "inputs": [
+                {"internalType": "address", "name": "spender", "type": "address"},
+                {"internalType": "uint256", "name": "amount", "type": "uint256"}
+            ],
+            "name": "approve",
+            "outputs": [{"internalType": "bool", "name": "", "type": "bool"}],
+            "stateMutability": "nonpayable",
+            "type": "function"
+        },
+        {
+            "inputs": [],
+            "name": "decimals",
+            "outputs": [{"internalType": "uint8", "name": "", "type": "uint8"}],
+            "stateMutability": "view",
+            "type": "function"
+        },
+        {
+            "inputs": [],
+            "name": "symbol",
+            "outputs": [{"internalType": "string", "name": "", "type": "string"}],
+            "stateMutability": "view",
+            "type": "function"
+        }
+    ]"#).unwrap()
+}
+
+// Convert a raw token amount into a decimal using the token's decimals
+pub fn to_decimal_units(amount: U256, decimals: u8) -> ArbitrageResult<Decimal> {
+    let formatted = ethers::utils::format_units(amount, decimals as u32)
+        .map_err(|e| ArbitrageError::Calculation(format!("Unit conversion error: {}", e)))?;
+    Decimal::from_str(&formatted)
+        .map_err(|e| ArbitrageError::Calculation(format!("Decimal conversion error: {}", e)))
+}
@@ -0,0 +1,161 @@
+use super::{DexPriceFetcher, PriceQuote, TokenPair};
+use crate::config::{PriceScript, PriceSpike, SyntheticDexConfig};
+use crate::errors::{ArbitrageError, Result};
+use async_trait::async_trait;
+use chrono::Utc;
+use ethers::types::BlockId;
+use rust_decimal::Decimal;
+use std::collections::HashMap;
+use std::fs;
+use std::sync::Mutex;
+
+// Per-pair position in a price script
+struct ScriptState {
+    tick: u64,
+    price: f64,
+    rng: u64,
+}
+
+// Chain-free price source driven by configured scripts, for tests and demos
+pub struct SyntheticFetcher {
+    config: SyntheticDexConfig,
+    csv_prices: HashMap<String, Vec<f64>>,
+    state: Mutex<HashMap<String, ScriptState>>,
+}
+
+impl SyntheticFetcher {
+    pub fn new(config: SyntheticDexConfig) -> Result<Self> {
+        let mut csv_prices = HashMap::new();
+        for (pair, script) in &config.pairs {
+            if let PriceScript::Csv { path } = script {
+                csv_prices.insert(pair.clone(), load_csv_prices(path)?);
+            }
+        }
+
+        Ok(Self {
+            config,
+            csv_prices,
+            state: Mutex::new(HashMap::new()),
+        })
+    }
+
+    fn next_price(&self, pair: &str, script: &PriceScript) -> Result<f64> {
+        let mut states = self.state.lock().unwrap();
+        let state = states.entry(pair.to_string()).or_insert_with(|| ScriptState {
+            tick: 0,
+            price: script.initial_price(),
+            rng: script.seed().max(1),
+        });
+
+        let price = match script {
+            PriceScript::Static { price } => *price,
+            PriceScript::RandomWalk { volatility_bps, .. } => {
+                if state.tick > 0 {
+                    let step = next_unit(&mut state.rng) * (*volatility_bps as f64) / 10_000.0;
+                    state.price *= 1.0 + step;
+                }
+                state.price
+            }
+            PriceScript::Csv { path } => {
+                let prices = &self.csv_prices[pair];
+                if prices.is_empty() {
+                    return Err(ArbitrageError::PriceFetch(format!("No prices in {}", path)));
+                }
+                // Replays the file in a loop
+                prices[(state.tick % prices.len() as u64) as usize]
+            }
+            PriceScript::Spikes { base, spikes } => apply_spikes(*base, spikes, state.tick),
+        };
+
+        state.tick += 1;
+        Ok(price)
+    }
+}
+
+#[async_trait]
+impl DexPriceFetcher for SyntheticFetcher {
+    async fn get_price_at(&self, token_pair: &TokenPair, _block: Option<BlockId>) -> Result<PriceQuote> {
+        let pair = format!("{}/{}", token_pair.token0.symbol, token_pair.token1.symbol);
+        let script = self.config.pairs.get(&pair).ok_or_else(|| {
+            ArbitrageError::PriceFetch(format!("{} has no script for {}", self.config.name, pair))
+        })?;
+
+        let price = Decimal::try_from(self.next_price(&pair, script)?)
+            .map_err(|e| ArbitrageError::Calculation(format!("Price conversion error: {}", e)))?;
+        // A spike of -100% or a zero in a CSV would otherwise reach the profit maths
+        if price <= Decimal::ZERO {
+            return Err(ArbitrageError::PriceFetch(format!(
+                "{} scripted a non-positive price {} for {}",
+                self.config.name, price, pair
+            )));
+        }
+        let liquidity = Decimal::try_from(self.config.liquidity)
+            .map_err(|e| ArbitrageError::Calculation(format!("Liquidity conversion error: {}", e)))?;
+
+        Ok(PriceQuote {
+            dex_name: self.config.name.clone(),
+            token_pair: pair,
+            price,
+            liquidity,
+            timestamp: Utc::now(),
+            block_number: None,
+            latency_ms: 0,
+        })
+    }
+
+    fn get_name(&self) -> &str {
+        &self.config.name
+    }
+}
+
+fn apply_spikes(base: f64, spikes: &[PriceSpike], tick: u64) -> f64 {
+    spikes
+        .iter()
+        .filter(|spike| tick >= spike.at_tick && tick < spike.at_tick + spike.duration_ticks.max(1))
+        .fold(base, |price, spike| price * (1.0 + spike.change_pct / 100.0))
+}
+
+// xorshift64, mapped to [-1, 1); deterministic for a given seed
+fn next_unit(state: &mut u64) -> f64 {
+    *state ^= *state << 13;
+    *state ^= *state >> 7;
+    *state ^= *state << 17;
+    (*state >> 11) as f64 / (1u64 << 52) as f64 - 1.0
+}
+
+// One price per line; the last comma-separated column is used and
+// non-numeric lines (headers) are skipped
+fn load_csv_prices(path: &str) -> Result<Vec<f64>> {
+    let content = fs::read_to_string(path)
+        .map_err(|e| ArbitrageError::Config(format!("Failed to read price script {}: {}", path, e)))?;
+
+    Ok(content
+        .lines()
+        .filter_map(|line| line.rsplit(',').next())
+        .filter_map(|value| value.trim().parse::<f64>().ok())
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::arbitrage::token_pairs;
+    use crate::config::tests::test_config;
+
+    #[tokio::test]
+    async fn non_positive_scripted_prices_fail_the_fetch() {
+        let crash = PriceSpike { at_tick: 1, duration_ticks: 1, change_pct: -100.0 };
+        let fetcher = SyntheticFetcher::new(SyntheticDexConfig {
+            name: "Synthetic".to_string(),
+            enabled: true,
+            liquidity: 1_000_000.0,
+            pairs: HashMap::from([("WETH/USDC".to_string(), PriceScript::Spikes { base: 1800.0, spikes: vec![crash] })]),
+        })
+        .unwrap();
+        let pair = &token_pairs(&test_config())[0];
+
+        assert_eq!(fetcher.get_price(pair).await.unwrap().price, Decimal::from(1800));
+        assert!(matches!(fetcher.get_price(pair).await, Err(ArbitrageError::PriceFetch(_))));
+        assert_eq!(fetcher.get_price(pair).await.unwrap().price, Decimal::from(1800));
+    }
+}
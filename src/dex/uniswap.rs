@@ -1,9 +1,10 @@
 use super::{DexPriceFetcher, PriceQuote, TokenPair, get_uniswap_v2_abi};
 use crate::config::DexConfig;
 use crate::errors::{ArbitrageError, Result};
+use crate::rpc::RpcProvider;
+use async_trait::async_trait;
 use ethers::{
-    providers::{Provider, Http},
-    types::{Address, U256},
+    types::{Address, BlockId, U256},
     contract::Contract,
 };
 use std::str::FromStr;
@@ -12,13 +13,13 @@ use rust_decimal::Decimal;
 use chrono::Utc;
 
 pub struct UniswapV2Fetcher {
-    router_contract: Contract<Arc<Provider<Http>>>,
+    router_contract: Contract<RpcProvider>,
     config: DexConfig,
 }
 
 impl UniswapV2Fetcher {
     pub async fn new(
-        provider: Arc<Provider<Http>>,
+        provider: Arc<RpcProvider>,
         config: DexConfig,
     ) -> Result<Self> {
         let router_address = Address::from_str(&config.router_address)
@@ -33,19 +34,22 @@ impl UniswapV2Fetcher {
         })
     }
     
-    async fn get_amounts_out(&self, amount_in: U256, path: Vec<Address>) -> Result<Vec<U256>> {
-        let amounts: Vec<U256> = self
+    async fn get_amounts_out(&self, amount_in: U256, path: Vec<Address>, block: Option<BlockId>) -> Result<Vec<U256>> {
+        let mut call = self
             .router_contract
-            .method::<_, Vec<U256>>("getAmountsOut", (amount_in, path))?
-            .call()
-            .await?;
+            .method::<_, Vec<U256>>("getAmountsOut", (amount_in, path))?;
+        if let Some(block) = block {
+            call = call.block(block);
+        }
+        let amounts: Vec<U256> = call.call().await?;
         
         Ok(amounts)
     }
 }
 
+#[async_trait]
 impl DexPriceFetcher for UniswapV2Fetcher {
-    async fn get_price(&self, token_pair: &TokenPair) -> Result<PriceQuote> {
+    async fn get_price_at(&self, token_pair: &TokenPair, block: Option<BlockId>) -> Result<PriceQuote> {
         let token0_address = Address::from_str(&token_pair.token0.address)
             .map_err(|e| ArbitrageError::Config(format!("Invalid token0 address: {}", e)))?;
         let token1_address = Address::from_str(&token_pair.token1.address)
@@ -55,7 +59,7 @@ impl DexPriceFetcher for UniswapV2Fetcher {
         let amount_in = U256::from(10u128.pow(token_pair.token0.decimals as u32));
         let path = vec![token0_address, token1_address];
         
-        let amounts = self.get_amounts_out(amount_in, path).await?;
+        let amounts = self.get_amounts_out(amount_in, path, block).await?;
         
         if amounts.len() < 2 {
             return Err(ArbitrageError::PriceFetch(
@@ -76,6 +80,8 @@ impl DexPriceFetcher for UniswapV2Fetcher {
             price,
             liquidity: Decimal::ZERO, // TODO: Implement liquidity calculation
             timestamp: Utc::now(),
+            block_number: None,
+            latency_ms: 0,
         })
     }
     
@@ -3,7 +3,7 @@ use thiserror::Error;
 #[derive(Error, Debug)]
 pub enum ArbitrageError {
     #[error("Database error: {0}")]
-    Database(#[from] sqlx::Error),
+    Database(String),
     
     #[error("Network error: {0}")]
     Network(#[from] reqwest::Error),
@@ -12,7 +12,10 @@ pub enum ArbitrageError {
     Ethereum(#[from] ethers::providers::ProviderError),
     
     #[error("Contract error: {0}")]
-    Contract(#[from] ethers::contract::ContractError<ethers::providers::Provider<ethers::providers::Http>>),
+    Contract(#[from] ethers::contract::ContractError<crate::rpc::RpcProvider>),
+    
+    #[error("ABI error: {0}")]
+    Abi(#[from] ethers::contract::AbiError),
     
     #[error("Configuration error: {0}")]
     Config(String),
@@ -23,6 +26,9 @@ pub enum ArbitrageError {
     #[error("Calculation error: {0}")]
     Calculation(String),
     
+    #[error("Submission error: {0}")]
+    Submission(String),
+    
     #[error("Invalid token pair: {from} -> {to}")]
     InvalidTokenPair { from: String, to: String },
 }
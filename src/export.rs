@@ -0,0 +1,377 @@
+use crate::database::{ArbitrageOpportunity, Database, OpportunityQuery, PriceSnapshot};
+use anyhow::{Context, Result};
+use chrono::{DateTime, SecondsFormat, Utc};
+use parquet::data_type::{ByteArray, ByteArrayType, Int64Type};
+use parquet::file::properties::WriterProperties;
+use parquet::file::writer::SerializedFileWriter;
+use parquet::schema::parser::parse_message_type;
+use serde_json::{Map, Value};
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::marker::PhantomData;
+use std::sync::Arc;
+
+// Rows buffered per Parquet row group; the only part of an export held in memory
+const PARQUET_ROW_GROUP_SIZE: usize = 65_536;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    Csv,
+    Jsonl,
+    Parquet,
+}
+
+impl std::str::FromStr for ExportFormat {
+    type Err = String;
+
+    fn from_str(value: &str) -> std::result::Result<Self, Self::Err> {
+        match value {
+            "csv" => Ok(ExportFormat::Csv),
+            "jsonl" => Ok(ExportFormat::Jsonl),
+            "parquet" => Ok(ExportFormat::Parquet),
+            _ => Err(format!("unknown format {:?}: expected csv, jsonl or parquet", value)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportTable {
+    Opportunities,
+    Prices,
+}
+
+impl std::str::FromStr for ExportTable {
+    type Err = String;
+
+    fn from_str(value: &str) -> std::result::Result<Self, Self::Err> {
+        match value {
+            "opportunities" => Ok(ExportTable::Opportunities),
+            "prices" => Ok(ExportTable::Prices),
+            _ => Err(format!("unknown table {:?}: expected opportunities or prices", value)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum ColumnType {
+    Integer,
+    OptionalInteger,
+    Timestamp,
+    // Decimals are exported as text too, so no format loses precision
+    Text,
+}
+
+#[derive(Debug, Clone)]
+enum Field {
+    Integer(Option<i64>),
+    Timestamp(DateTime<Utc>),
+    Text(String),
+}
+
+trait ExportRow {
+    const NAME: &'static str;
+    const COLUMNS: &'static [(&'static str, ColumnType)];
+
+    fn fields(self) -> Vec<Field>;
+}
+
+impl ExportRow for ArbitrageOpportunity {
+    const NAME: &'static str = "arbitrage_opportunities";
+    const COLUMNS: &'static [(&'static str, ColumnType)] = &[
+        ("id", ColumnType::Integer),
+        ("timestamp", ColumnType::Timestamp),
+        ("token_pair", ColumnType::Text),
+        ("buy_dex", ColumnType::Text),
+        ("sell_dex", ColumnType::Text),
+        ("buy_price", ColumnType::Text),
+        ("sell_price", ColumnType::Text),
+        ("price_difference", ColumnType::Text),
+        ("profit_usd", ColumnType::Text),
+        ("profit_percentage", ColumnType::Text),
+        ("trade_amount", ColumnType::Text),
+        ("gas_cost_usd", ColumnType::Text),
+    ];
+
+    fn fields(self) -> Vec<Field> {
+        vec![
+            Field::Integer(self.id),
+            Field::Timestamp(self.timestamp),
+            Field::Text(self.token_pair),
+            Field::Text(self.buy_dex),
+            Field::Text(self.sell_dex),
+            Field::Text(self.buy_price.to_string()),
+            Field::Text(self.sell_price.to_string()),
+            Field::Text(self.price_difference.to_string()),
+            Field::Text(self.profit_usd.to_string()),
+            Field::Text(self.profit_percentage.to_string()),
+            Field::Text(self.trade_amount.to_string()),
+            Field::Text(self.gas_cost_usd.to_string()),
+        ]
+    }
+}
+
+impl ExportRow for PriceSnapshot {
+    const NAME: &'static str = "price_snapshots";
+    const COLUMNS: &'static [(&'static str, ColumnType)] = &[
+        ("id", ColumnType::Integer),
+        ("timestamp", ColumnType::Timestamp),
+        ("dex_name", ColumnType::Text),
+        ("token_pair", ColumnType::Text),
+        ("block_number", ColumnType::OptionalInteger),
+        ("price", ColumnType::Text),
+        ("liquidity", ColumnType::Text),
+        ("latency_ms", ColumnType::Integer),
+    ];
+
+    fn fields(self) -> Vec<Field> {
+        vec![
+            Field::Integer(self.id),
+            Field::Timestamp(self.timestamp),
+            Field::Text(self.dex_name),
+            Field::Text(self.token_pair),
+            Field::Integer(self.block_number),
+            Field::Text(self.price.to_string()),
+            Field::Text(self.liquidity.to_string()),
+            Field::Integer(Some(self.latency_ms)),
+        ]
+    }
+}
+
+enum Sink {
+    Csv(csv::Writer<BufWriter<File>>),
+    Jsonl(BufWriter<File>),
+    Parquet {
+        writer: SerializedFileWriter<File>,
+        buffered: Vec<Vec<Field>>,
+    },
+}
+
+// Writes rows as they arrive from the database stream
+struct Exporter<R: ExportRow> {
+    sink: Sink,
+    rows: usize,
+    _row: PhantomData<R>,
+}
+
+impl<R: ExportRow> Exporter<R> {
+    fn create(path: &str, format: ExportFormat) -> Result<Self> {
+        let file = File::create(path).with_context(|| format!("Failed to create {}", path))?;
+
+        let sink = match format {
+            ExportFormat::Csv => {
+                let mut writer = csv::Writer::from_writer(BufWriter::new(file));
+                writer.write_record(R::COLUMNS.iter().map(|(name, _)| *name))?;
+                Sink::Csv(writer)
+            }
+            ExportFormat::Jsonl => Sink::Jsonl(BufWriter::new(file)),
+            ExportFormat::Parquet => {
+                let schema = Arc::new(parse_message_type(&parquet_schema::<R>())?);
+                let properties = Arc::new(WriterProperties::builder().build());
+                Sink::Parquet {
+                    writer: SerializedFileWriter::new(file, schema, properties)?,
+                    buffered: Vec::new(),
+                }
+            }
+        };
+
+        Ok(Self { sink, rows: 0, _row: PhantomData })
+    }
+
+    fn write(&mut self, row: R) -> Result<()> {
+        let fields = row.fields();
+
+        match &mut self.sink {
+            Sink::Csv(writer) => {
+                writer.write_record(fields.iter().map(|field| match field {
+                    Field::Integer(value) => value.map(|value| value.to_string()).unwrap_or_default(),
+                    Field::Timestamp(value) => value.to_rfc3339_opts(SecondsFormat::Micros, true),
+                    Field::Text(value) => value.clone(),
+                }))?;
+            }
+            Sink::Jsonl(writer) => {
+                let object: Map<String, Value> = R::COLUMNS
+                    .iter()
+                    .zip(fields)
+                    .map(|((name, _), field)| {
+                        let value = match field {
+                            Field::Integer(value) => value.map_or(Value::Null, Value::from),
+                            Field::Timestamp(value) => Value::from(value.to_rfc3339_opts(SecondsFormat::Micros, true)),
+                            Field::Text(value) => Value::from(value),
+                        };
+                        (name.to_string(), value)
+                    })
+                    .collect();
+                serde_json::to_writer(&mut *writer, &object)?;
+                writer.write_all(b"\n")?;
+            }
+            Sink::Parquet { writer, buffered } => {
+                buffered.push(fields);
+                if buffered.len() >= PARQUET_ROW_GROUP_SIZE {
+                    write_row_group::<R>(writer, buffered)?;
+                }
+            }
+        }
+
+        self.rows += 1;
+        Ok(())
+    }
+
+    fn finish(self) -> Result<usize> {
+        match self.sink {
+            Sink::Csv(mut writer) => writer.flush()?,
+            Sink::Jsonl(mut writer) => writer.flush()?,
+            Sink::Parquet { mut writer, mut buffered } => {
+                if !buffered.is_empty() {
+                    write_row_group::<R>(&mut writer, &mut buffered)?;
+                }
+                writer.close()?;
+            }
+        }
+
+        Ok(self.rows)
+    }
+}
+
+fn parquet_schema<R: ExportRow>() -> String {
+    let columns: String = R::COLUMNS
+        .iter()
+        .map(|(name, column_type)| match column_type {
+            ColumnType::Integer => format!("REQUIRED INT64 {};", name),
+            ColumnType::OptionalInteger => format!("OPTIONAL INT64 {};", name),
+            ColumnType::Timestamp => format!("REQUIRED INT64 {} (TIMESTAMP(MICROS,true));", name),
+            ColumnType::Text => format!("REQUIRED BINARY {} (STRING);", name),
+        })
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    format!("message {} {{ {} }}", R::NAME, columns)
+}
+
+// Flushes buffered rows as one row group, column by column
+fn write_row_group<R: ExportRow>(writer: &mut SerializedFileWriter<File>, buffered: &mut Vec<Vec<Field>>) -> Result<()> {
+    let mut row_group = writer.next_row_group()?;
+    let mut index = 0;
+
+    while let Some(mut column) = row_group.next_column()? {
+        match R::COLUMNS[index].1 {
+            ColumnType::Integer | ColumnType::Timestamp => {
+                let values: Vec<i64> = buffered
+                    .iter()
+                    .map(|fields| match &fields[index] {
+                        Field::Integer(value) => value.unwrap_or_default(),
+                        Field::Timestamp(value) => value.timestamp_micros(),
+                        Field::Text(_) => unreachable!("text field in an integer column"),
+                    })
+                    .collect();
+                column.typed::<Int64Type>().write_batch(&values, None, None)?;
+            }
+            ColumnType::OptionalInteger => {
+                let present: Vec<Option<i64>> = buffered
+                    .iter()
+                    .map(|fields| match &fields[index] {
+                        Field::Integer(value) => *value,
+                        _ => unreachable!("non-integer field in an integer column"),
+                    })
+                    .collect();
+                let values: Vec<i64> = present.iter().flatten().copied().collect();
+                let definition_levels: Vec<i16> = present.iter().map(|value| value.is_some() as i16).collect();
+                column.typed::<Int64Type>().write_batch(&values, Some(&definition_levels), None)?;
+            }
+            ColumnType::Text => {
+                let values: Vec<ByteArray> = buffered
+                    .iter()
+                    .map(|fields| match &fields[index] {
+                        Field::Text(value) => ByteArray::from(value.as_bytes().to_vec()),
+                        _ => unreachable!("non-text field in a text column"),
+                    })
+                    .collect();
+                column.typed::<ByteArrayType>().write_batch(&values, None, None)?;
+            }
+        }
+
+        column.close()?;
+        index += 1;
+    }
+
+    row_group.close()?;
+    buffered.clear();
+    Ok(())
+}
+
+pub async fn export_opportunities(database: &Database, query: &OpportunityQuery, format: ExportFormat, path: &str) -> Result<usize> {
+    let mut exporter = Exporter::<ArbitrageOpportunity>::create(path, format)?;
+    database.for_each_opportunity(query, |opportunity| exporter.write(opportunity)).await?;
+    exporter.finish()
+}
+
+pub async fn export_price_snapshots(
+    database: &Database,
+    token_pair: Option<&str>,
+    dex_name: Option<&str>,
+    from: Option<DateTime<Utc>>,
+    to: Option<DateTime<Utc>>,
+    format: ExportFormat,
+    path: &str,
+) -> Result<usize> {
+    let mut exporter = Exporter::<PriceSnapshot>::create(path, format)?;
+    database
+        .for_each_price_snapshot(token_pair, dex_name, from, to, |snapshot| exporter.write(snapshot))
+        .await?;
+    exporter.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use parquet::file::reader::{FileReader, SerializedFileReader};
+    use parquet::record::RowAccessor;
+    use rust_decimal::Decimal;
+    use std::str::FromStr;
+
+    fn snapshot(block_number: Option<i64>) -> PriceSnapshot {
+        PriceSnapshot {
+            id: Some(7),
+            timestamp: DateTime::parse_from_rfc3339("2026-10-18T12:00:00Z").unwrap().with_timezone(&Utc),
+            dex_name: "QuickSwap".to_string(),
+            token_pair: "WETH/USDC".to_string(),
+            block_number,
+            price: Decimal::from_str("1834.123456789012345678").unwrap(),
+            liquidity: Decimal::from(250_000),
+            latency_ms: 42,
+        }
+    }
+
+    fn export(format: ExportFormat) -> String {
+        let path = std::env::temp_dir().join(format!("arbitrage-export-{:?}-{}", format, std::process::id()));
+        let path = path.to_str().unwrap().to_string();
+
+        let mut exporter = Exporter::<PriceSnapshot>::create(&path, format).unwrap();
+        exporter.write(snapshot(Some(100))).unwrap();
+        exporter.write(snapshot(None)).unwrap();
+        assert_eq!(exporter.finish().unwrap(), 2);
+
+        path
+    }
+
+    #[test]
+    fn exports_keep_decimals_exact_in_every_format() {
+        let csv = std::fs::read_to_string(export(ExportFormat::Csv)).unwrap();
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines[0], "id,timestamp,dex_name,token_pair,block_number,price,liquidity,latency_ms");
+        assert_eq!(lines[1], "7,2026-10-18T12:00:00.000000Z,QuickSwap,WETH/USDC,100,1834.123456789012345678,250000,42");
+        assert_eq!(lines[2], "7,2026-10-18T12:00:00.000000Z,QuickSwap,WETH/USDC,,1834.123456789012345678,250000,42");
+
+        let jsonl = std::fs::read_to_string(export(ExportFormat::Jsonl)).unwrap();
+        let rows: Vec<Value> = jsonl.lines().map(|line| serde_json::from_str(line).unwrap()).collect();
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0]["price"], "1834.123456789012345678");
+        assert_eq!(rows[0]["block_number"], 100);
+        assert_eq!(rows[1]["block_number"], Value::Null);
+
+        let reader = SerializedFileReader::new(File::open(export(ExportFormat::Parquet)).unwrap()).unwrap();
+        assert_eq!(reader.metadata().file_metadata().num_rows(), 2);
+        let row = reader.get_row_iter(None).unwrap().next().unwrap().unwrap();
+        assert_eq!(row.get_string(5).unwrap(), "1834.123456789012345678");
+        assert_eq!(row.get_long(4).unwrap(), 100);
+    }
+}
@@ -0,0 +1,216 @@
+use crate::config::Config;
+use crate::database::Database;
+use crate::rpc::RpcProvider;
+use ethers::providers::Middleware;
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use std::sync::{Arc, LazyLock, Mutex};
+use std::time::{Duration, Instant};
+use tokio::time;
+
+// Fed by the detector and DexManager as they run, read by the probes
+pub static HEALTH: LazyLock<HealthState> = LazyLock::new(HealthState::new);
+
+// Two live quotes are the least a spread can be computed from
+const MIN_HEALTHY_FETCHERS: usize = 2;
+
+pub struct HealthState {
+    started: Instant,
+    inner: Mutex<Inner>,
+}
+
+#[derive(Default)]
+struct Inner {
+    last_cycle: Option<Instant>,
+    last_cycle_error: Option<String>,
+    fetchers: BTreeMap<String, FetcherStatus>,
+}
+
+#[derive(Default)]
+struct FetcherStatus {
+    last_quote: Option<Instant>,
+    last_error: Option<String>,
+    consecutive_failures: u64,
+}
+
+impl HealthState {
+    fn new() -> Self {
+        Self {
+            started: Instant::now(),
+            inner: Mutex::new(Inner::default()),
+        }
+    }
+
+    pub fn record_cycle(&self, error: Option<String>) {
+        let mut inner = self.inner.lock().unwrap();
+        if error.is_none() {
+            inner.last_cycle = Some(Instant::now());
+        }
+        inner.last_cycle_error = error;
+    }
+
+    // Called when DexManager is (re)built so removed fetchers stop counting
+    pub fn track_fetchers<'a>(&self, names: impl IntoIterator<Item = &'a str>) {
+        let mut inner = self.inner.lock().unwrap();
+        let mut fetchers = BTreeMap::new();
+        for name in names {
+            let status = inner.fetchers.remove(name).unwrap_or_default();
+            fetchers.insert(name.to_string(), status);
+        }
+        inner.fetchers = fetchers;
+    }
+
+    pub fn record_quote(&self, fetcher: &str, error: Option<String>) {
+        let mut inner = self.inner.lock().unwrap();
+        let status = inner.fetchers.entry(fetcher.to_string()).or_default();
+        match error {
+            None => {
+                status.last_quote = Some(Instant::now());
+                status.consecutive_failures = 0;
+            }
+            Some(error) => {
+                status.last_error = Some(error);
+                status.consecutive_failures += 1;
+            }
+        }
+    }
+
+    fn cycle_check(&self, max_age: Duration, startup_grace: bool) -> (bool, Value) {
+        let inner = self.inner.lock().unwrap();
+        let age = inner.last_cycle.map(|last| last.elapsed());
+        let ok = match age {
+            Some(age) => age <= max_age,
+            None => startup_grace && self.started.elapsed() <= max_age,
+        };
+        (ok, json!({
+            "ok": ok,
+            "seconds_since_last_cycle": age.map(|age| age.as_secs()),
+            "last_error": inner.last_cycle_error,
+        }))
+    }
+
+    fn fetcher_check(&self, max_age: Duration) -> (bool, Value) {
+        let inner = self.inner.lock().unwrap();
+        let mut healthy = 0;
+        let mut by_name = serde_json::Map::new();
+        for (name, status) in &inner.fetchers {
+            let age = status.last_quote.map(|last| last.elapsed());
+            let ok = age.is_some_and(|age| age <= max_age);
+            healthy += usize::from(ok);
+            by_name.insert(name.clone(), json!({
+                "ok": ok,
+                "seconds_since_last_quote": age.map(|age| age.as_secs()),
+                "consecutive_failures": status.consecutive_failures,
+                "last_error": status.last_error,
+            }));
+        }
+        let ok = healthy >= MIN_HEALTHY_FETCHERS;
+        (ok, json!({ "ok": ok, "healthy": healthy, "by_name": by_name }))
+    }
+}
+
+pub struct HealthChecks {
+    max_cycle_age: Duration,
+    max_quote_age: Duration,
+    timeout: Duration,
+    database: Database,
+    provider: Option<Arc<RpcProvider>>, // None when nothing is read from a live chain
+}
+
+impl HealthChecks {
+    // Unset ages scale with the check interval: three missed cycles for the
+    // loop, five for a fetcher, and never under a minute
+    pub fn new(config: &Config, database: Database, provider: Option<Arc<RpcProvider>>) -> Self {
+        let interval = config.general.check_interval;
+        let or_derived = |secs: u64, cycles: u64| Duration::from_secs(if secs > 0 { secs } else { (interval * cycles).max(60) });
+        Self {
+            max_cycle_age: or_derived(config.health.max_cycle_age_secs, 3),
+            max_quote_age: or_derived(config.health.max_quote_age_secs, 5),
+            timeout: Duration::from_secs(config.health.check_timeout_secs),
+            database,
+            provider,
+        }
+    }
+    
+    // Liveness: the process is up and the detection loop is still completing
+    // cycles. Before the first cycle the loop gets the same allowance to start.
+    pub fn liveness(&self) -> (bool, Value) {
+        let (ok, cycle) = HEALTH.cycle_check(self.max_cycle_age, true);
+        (ok, json!({
+            "status": if ok { "ok" } else { "stale" },
+            "uptime_secs": HEALTH.started.elapsed().as_secs(),
+            "cycle": cycle,
+        }))
+    }
+
+    // Readiness: a recent successful cycle, a reachable RPC endpoint, a
+    // writable database and enough fetchers returning quotes
+    pub async fn readiness(&self) -> (bool, Value) {
+        let (cycle_ok, cycle) = HEALTH.cycle_check(self.max_cycle_age, false);
+        let (fetchers_ok, fetchers) = HEALTH.fetcher_check(self.max_quote_age);
+        let (rpc_ok, rpc) = self.rpc_check().await;
+        let (database_ok, database) = self.database_check().await;
+
+        let ok = cycle_ok && fetchers_ok && rpc_ok && database_ok;
+        (ok, json!({
+            "status": if ok { "ready" } else { "not_ready" },
+            "checks": {
+                "cycle": cycle,
+                "rpc": rpc,
+                "database": database,
+                "fetchers": fetchers,
+            },
+        }))
+    }
+
+    async fn rpc_check(&self) -> (bool, Value) {
+        let Some(provider) = &self.provider else {
+            return (true, json!({ "ok": true, "skipped": "no live RPC in use" }));
+        };
+
+        let started = Instant::now();
+        let timeout = self.timeout;
+        match time::timeout(timeout, provider.get_block_number()).await {
+            Ok(Ok(block)) => (true, json!({
+                "ok": true,
+                "block": block.as_u64(),
+                "latency_ms": started.elapsed().as_millis() as u64,
+            })),
+            Ok(Err(e)) => (false, json!({ "ok": false, "error": e.to_string() })),
+            Err(_) => (false, json!({ "ok": false, "error": format!("timed out after {}s", timeout.as_secs()) })),
+        }
+    }
+
+    async fn database_check(&self) -> (bool, Value) {
+        let timeout = self.timeout;
+        match time::timeout(timeout, self.database.write_heartbeat()).await {
+            Ok(Ok(())) => (true, json!({ "ok": true, "backend": self.database.backend() })),
+            Ok(Err(e)) => (false, json!({ "ok": false, "error": e.to_string() })),
+            Err(_) => (false, json!({ "ok": false, "error": format!("timed out after {}s", timeout.as_secs()) })),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn readiness_needs_two_fetchers_with_fresh_quotes() {
+        let state = HealthState::new();
+        state.track_fetchers(["QuickSwap", "SushiSwap", "Removed"]);
+        state.track_fetchers(["QuickSwap", "SushiSwap"]);
+        state.record_quote("QuickSwap", None);
+        state.record_quote("SushiSwap", Some("execution reverted".to_string()));
+
+        let (ok, status) = state.fetcher_check(Duration::from_secs(60));
+        assert!(!ok);
+        assert_eq!(status["healthy"], 1);
+        assert_eq!(status["by_name"]["SushiSwap"]["consecutive_failures"], 1);
+        assert!(status["by_name"].get("Removed").is_none());
+
+        state.record_quote("SushiSwap", None);
+        assert!(state.fetcher_check(Duration::from_secs(60)).0);
+        assert!(!state.fetcher_check(Duration::ZERO).0);
+    }
+}
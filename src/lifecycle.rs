@@ -0,0 +1,222 @@
+use crate::arbitrage::RejectionReason;
+use crate::database::{ArbitrageOpportunity, Database, OpportunityLifecycle};
+use chrono::Utc;
+use std::collections::HashMap;
+use tracing::{info, warn};
+
+// What one detection cycle saw for a token pair
+#[derive(Debug, Clone)]
+pub enum PairObservation {
+    Opportunity {
+        opportunity: Box<ArbitrageOpportunity>,
+        block: Option<u64>,
+    },
+    Rejected(RejectionReason),
+    NoQuotes,
+}
+
+// Follows each pair's opportunity across cycles so that a spread that
+// persists for several ticks is one lifecycle rather than many rows.
+// A lifecycle stays open while the same buy/sell route keeps qualifying.
+pub struct LifecycleTracker {
+    open: HashMap<String, OpportunityLifecycle>,
+}
+
+impl LifecycleTracker {
+    pub async fn new(database: &Database) -> Self {
+        // Anything left open by a previous run can no longer be observed
+        match database.close_open_lifecycles("bot_restarted").await {
+            Ok(0) => {}
+            Ok(closed) => info!("Closed {} opportunity lifecycles left open by a previous run", closed),
+            Err(e) => warn!("Failed to close stale opportunity lifecycles: {}", e),
+        }
+
+        Self { open: HashMap::new() }
+    }
+
+    pub async fn observe(&mut self, database: &Database, token_pair: &str, observation: &PairObservation) {
+        let result = match observation {
+            PairObservation::Opportunity { opportunity, block } => {
+                self.observe_opportunity(database, token_pair, opportunity, *block).await
+            }
+            PairObservation::Rejected(reason) => self.close(database, token_pair, reason.as_str()).await,
+            PairObservation::NoQuotes => self.close(database, token_pair, "no_quotes").await,
+        };
+
+        if let Err(e) = result {
+            warn!("Failed to update opportunity lifecycle for {}: {}", token_pair, e);
+        }
+    }
+
+    async fn observe_opportunity(
+        &mut self,
+        database: &Database,
+        token_pair: &str,
+        opportunity: &ArbitrageOpportunity,
+        block: Option<u64>,
+    ) -> anyhow::Result<()> {
+        if let Some(lifecycle) = self.open.get_mut(token_pair) {
+            if lifecycle.buy_dex == opportunity.buy_dex && lifecycle.sell_dex == opportunity.sell_dex {
+                lifecycle.last_seen = opportunity.timestamp;
+                lifecycle.last_block = block.map(|b| b as i64).or(lifecycle.last_block);
+                lifecycle.observations += 1;
+                lifecycle.last_profit_usd = opportunity.profit_usd;
+                lifecycle.peak_profit_usd = lifecycle.peak_profit_usd.max(opportunity.profit_usd);
+                return database.update_lifecycle(lifecycle).await;
+            }
+
+            self.close(database, token_pair, "route_changed").await?;
+        }
+
+        let mut lifecycle = OpportunityLifecycle {
+            id: None,
+            token_pair: token_pair.to_string(),
+            buy_dex: opportunity.buy_dex.clone(),
+            sell_dex: opportunity.sell_dex.clone(),
+            first_seen: opportunity.timestamp,
+            last_seen: opportunity.timestamp,
+            first_block: block.map(|b| b as i64),
+            last_block: block.map(|b| b as i64),
+            observations: 1,
+            initial_profit_usd: opportunity.profit_usd,
+            peak_profit_usd: opportunity.profit_usd,
+            last_profit_usd: opportunity.profit_usd,
+            close_reason: None,
+            closed_at: None,
+        };
+        lifecycle.id = Some(database.insert_lifecycle(&lifecycle).await?);
+        self.open.insert(token_pair.to_string(), lifecycle);
+
+        Ok(())
+    }
+
+    async fn close(&mut self, database: &Database, token_pair: &str, reason: &str) -> anyhow::Result<()> {
+        let Some(mut lifecycle) = self.open.remove(token_pair) else {
+            return Ok(());
+        };
+
+        lifecycle.close_reason = Some(reason.to_string());
+        lifecycle.closed_at = Some(Utc::now());
+        database.update_lifecycle(&lifecycle).await?;
+
+        info!(
+            "Opportunity {} -> {} on {} closed ({}) after {} observations over {}s, peak profit ${:.2}",
+            lifecycle.buy_dex,
+            lifecycle.sell_dex,
+            lifecycle.token_pair,
+            reason,
+            lifecycle.observations,
+            (lifecycle.last_seen - lifecycle.first_seen).num_seconds(),
+            lifecycle.peak_profit_usd
+        );
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::tests::{lifecycle_rows, opportunity, temp_database};
+    use rust_decimal::Decimal;
+    use std::str::FromStr;
+
+    fn seen(buy_dex: &str, profit_usd: &str, block: u64) -> PairObservation {
+        PairObservation::Opportunity {
+            opportunity: Box::new(opportunity("WETH/USDC", buy_dex, profit_usd, Utc::now())),
+            block: Some(block),
+        }
+    }
+
+    fn usd(value: &str) -> Decimal {
+        Decimal::from_str(value).unwrap()
+    }
+
+    #[tokio::test]
+    async fn a_persisting_route_is_one_lifecycle() {
+        let database = temp_database("lifecycle-persist").await;
+        let mut tracker = LifecycleTracker::new(&database).await;
+
+        for (profit, block) in [("5", 100), ("8", 101), ("6", 102)] {
+            tracker.observe(&database, "WETH/USDC", &seen("QuickSwap", profit, block)).await;
+        }
+
+        let rows = lifecycle_rows(&database).await;
+        assert_eq!(rows.len(), 1);
+        let lifecycle = &rows[0];
+        assert_eq!((lifecycle.buy_dex.as_str(), lifecycle.sell_dex.as_str()), ("QuickSwap", "SushiSwap"));
+        assert_eq!(lifecycle.observations, 3);
+        assert_eq!((lifecycle.first_block, lifecycle.last_block), (Some(100), Some(102)));
+        assert_eq!(
+            (lifecycle.initial_profit_usd, lifecycle.peak_profit_usd, lifecycle.last_profit_usd),
+            (usd("5"), usd("8"), usd("6"))
+        );
+        assert!(lifecycle.first_seen <= lifecycle.last_seen);
+        assert_eq!((lifecycle.close_reason.as_deref(), lifecycle.closed_at), (None, None));
+    }
+
+    #[tokio::test]
+    async fn lifecycles_close_with_the_reason_they_ended() {
+        let database = temp_database("lifecycle-close").await;
+        let mut tracker = LifecycleTracker::new(&database).await;
+
+        tracker.observe(&database, "WETH/USDC", &seen("QuickSwap", "5", 100)).await;
+        tracker.observe(&database, "WETH/USDC", &seen("Uniswap V2", "4", 101)).await;
+        tracker
+            .observe(&database, "WETH/USDC", &PairObservation::Rejected(RejectionReason::BelowUsdThreshold))
+            .await;
+        tracker.observe(&database, "WETH/USDC", &seen("QuickSwap", "3", 103)).await;
+        tracker.observe(&database, "WETH/USDC", &PairObservation::NoQuotes).await;
+        // Nothing is open, so further gaps record nothing
+        tracker.observe(&database, "WETH/USDC", &PairObservation::NoQuotes).await;
+
+        let closed: Vec<(String, Option<String>, i64)> = lifecycle_rows(&database)
+            .await
+            .into_iter()
+            .map(|lifecycle| {
+                assert!(lifecycle.closed_at.is_some());
+                (lifecycle.buy_dex, lifecycle.close_reason, lifecycle.observations)
+            })
+            .collect();
+        assert_eq!(
+            closed,
+            vec![
+                ("QuickSwap".to_string(), Some("route_changed".to_string()), 1),
+                ("Uniswap V2".to_string(), Some("below_usd_threshold".to_string()), 1),
+                ("QuickSwap".to_string(), Some("no_quotes".to_string()), 1),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn pairs_are_tracked_independently() {
+        let database = temp_database("lifecycle-pairs").await;
+        let mut tracker = LifecycleTracker::new(&database).await;
+
+        tracker.observe(&database, "WETH/USDC", &seen("QuickSwap", "5", 100)).await;
+        tracker.observe(&database, "WBTC/USDC", &seen("QuickSwap", "9", 100)).await;
+        tracker.observe(&database, "WBTC/USDC", &PairObservation::NoQuotes).await;
+        tracker.observe(&database, "WETH/USDC", &seen("QuickSwap", "6", 101)).await;
+
+        let rows = lifecycle_rows(&database).await;
+        let states: Vec<(&str, i64, Option<&str>)> = rows
+            .iter()
+            .map(|lifecycle| (lifecycle.token_pair.as_str(), lifecycle.observations, lifecycle.close_reason.as_deref()))
+            .collect();
+        assert_eq!(states, vec![("WETH/USDC", 2, None), ("WBTC/USDC", 1, Some("no_quotes"))]);
+    }
+
+    #[tokio::test]
+    async fn a_restart_closes_lifecycles_left_open() {
+        let database = temp_database("lifecycle-restart").await;
+        let mut tracker = LifecycleTracker::new(&database).await;
+        tracker.observe(&database, "WETH/USDC", &seen("QuickSwap", "5", 100)).await;
+        drop(tracker);
+
+        let mut tracker = LifecycleTracker::new(&database).await;
+        tracker.observe(&database, "WETH/USDC", &seen("QuickSwap", "5", 101)).await;
+
+        let reasons: Vec<Option<String>> = lifecycle_rows(&database).await.into_iter().map(|l| l.close_reason).collect();
+        assert_eq!(reasons, vec![Some("bot_restarted".to_string()), None]);
+    }
+}
@@ -0,0 +1,393 @@
+use crate::config::{GeneralConfig, LogFileConfig, LogFormat, LogRotation};
+use anyhow::{anyhow, Context, Result};
+use chrono::{NaiveDate, SecondsFormat, Utc};
+use serde_json::{Map, Value};
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tracing::field::{Field, Visit};
+use tracing::{warn, Event, Subscriber};
+use tracing_subscriber::filter::{LevelFilter, Targets};
+use tracing_subscriber::fmt::format::Writer;
+use tracing_subscriber::fmt::{self, FmtContext, FormatEvent, FormatFields};
+use tracing_subscriber::layer::{Layered, SubscriberExt};
+use tracing_subscriber::registry::{LookupSpan, Registry};
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::{reload, Layer};
+
+type Filtered = Layered<reload::Layer<Targets, Registry>, Registry>;
+type OutputLayer = Box<dyn Layer<Filtered> + Send + Sync>;
+
+// Lets a reloaded configuration change the level filter; the output format
+// and file are fixed once the subscriber is installed
+pub struct LogHandle {
+    filter: reload::Handle<Targets, Registry>,
+    format: LogFormat,
+    file: Option<LogFileConfig>,
+}
+
+impl LogHandle {
+    pub fn apply(&self, config: &GeneralConfig) -> Result<()> {
+        self.filter
+            .reload(parse_level(&config.log_level)?)
+            .map_err(|e| anyhow!("cannot change the log level: {}", e))?;
+
+        if config.log_format != self.format || config.log_file != self.file {
+            warn!("Changes to general.log_format and general.log_file take effect after a restart");
+        }
+        Ok(())
+    }
+}
+
+pub fn parse_level(level: &str) -> Result<Targets> {
+    // A bare word is a target enabled at every level to Targets, which turns
+    // a typo such as "inf" into trace logging for nothing
+    for directive in level.split(',').map(str::trim).filter(|directive| !directive.is_empty()) {
+        if !directive.contains('=') && directive.parse::<LevelFilter>().is_err() {
+            return Err(anyhow!("invalid log level {:?}: {:?} is not a level", level, directive));
+        }
+    }
+    level.parse().map_err(|e| anyhow!("invalid log level {:?}: {}", level, e))
+}
+
+pub fn init(config: &GeneralConfig) -> Result<LogHandle> {
+    // An invalid level is reported by config validation along with everything else
+    let level = parse_level(&config.log_level).unwrap_or_else(|_| Targets::new().with_default(LevelFilter::INFO));
+    let (filter, handle) = reload::Layer::new(level);
+
+    let mut outputs: Vec<OutputLayer> = vec![output_layer(config.log_format, true, io::stdout)];
+    if let Some(file) = &config.log_file {
+        let writer = Mutex::new(RollingFile::open(file)?);
+        outputs.push(output_layer(config.log_format, false, writer));
+    }
+
+    tracing_subscriber::registry()
+        .with(filter)
+        .with(outputs)
+        .try_init()
+        .context("a global logger is already installed")?;
+
+    Ok(LogHandle {
+        filter: handle,
+        format: config.log_format,
+        file: config.log_file.clone(),
+    })
+}
+
+fn output_layer<W>(format: LogFormat, ansi: bool, writer: W) -> OutputLayer
+where
+    W: for<'writer> fmt::MakeWriter<'writer> + Send + Sync + 'static,
+{
+    let layer = fmt::layer().with_ansi(ansi).with_writer(writer);
+    match format {
+        LogFormat::Text => layer.boxed(),
+        LogFormat::Json => layer.event_format(JsonFormat).boxed(),
+    }
+}
+
+// One JSON object per line: timestamp, level, target, message and every
+// structured field of the event at the top level
+struct JsonFormat;
+
+impl<S, N> FormatEvent<S, N> for JsonFormat
+where
+    S: Subscriber + for<'lookup> LookupSpan<'lookup>,
+    N: for<'writer> FormatFields<'writer> + 'static,
+{
+    fn format_event(&self, ctx: &FmtContext<'_, S, N>, mut writer: Writer<'_>, event: &Event<'_>) -> std::fmt::Result {
+        let metadata = event.metadata();
+        let mut object = Map::new();
+        object.insert("timestamp".into(), Utc::now().to_rfc3339_opts(SecondsFormat::Micros, true).into());
+        object.insert("level".into(), metadata.level().as_str().into());
+        object.insert("target".into(), metadata.target().into());
+
+        if let Some(scope) = ctx.event_scope() {
+            let spans: Vec<Value> = scope.from_root().map(|span| span.name().into()).collect();
+            object.insert("spans".into(), spans.into());
+        }
+
+        event.record(&mut JsonFields(&mut object));
+        writeln!(writer, "{}", Value::Object(object))
+    }
+}
+
+struct JsonFields<'a>(&'a mut Map<String, Value>);
+
+impl Visit for JsonFields<'_> {
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        self.0.insert(field.name().into(), format!("{:?}", value).into());
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.0.insert(field.name().into(), value.into());
+    }
+
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        self.0.insert(field.name().into(), value.into());
+    }
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        self.0.insert(field.name().into(), value.into());
+    }
+
+    fn record_f64(&mut self, field: &Field, value: f64) {
+        self.0.insert(field.name().into(), value.into());
+    }
+
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        self.0.insert(field.name().into(), value.into());
+    }
+}
+
+// Appends to `<path>.<period>` and starts a new file when the period rolls
+// over, deleting the oldest beyond `max_files`
+struct RollingFile {
+    path: PathBuf,
+    rotation: LogRotation,
+    max_files: usize,
+    period: String,
+    file: File,
+}
+
+impl RollingFile {
+    fn open(config: &LogFileConfig) -> Result<Self> {
+        let path = PathBuf::from(&config.path);
+        if let Some(directory) = path.parent().filter(|directory| !directory.as_os_str().is_empty()) {
+            fs::create_dir_all(directory).with_context(|| format!("cannot create log directory {}", directory.display()))?;
+        }
+
+        let period = period(config.rotation);
+        let file = open_append(&file_for(&path, &period))
+            .with_context(|| format!("cannot open log file {}", config.path))?;
+        let rolling = Self {
+            path,
+            rotation: config.rotation,
+            max_files: config.max_files,
+            period,
+            file,
+        };
+        rolling.remove_old_files();
+        Ok(rolling)
+    }
+
+    fn roll(&mut self) -> io::Result<()> {
+        let period = period(self.rotation);
+        if period != self.period {
+            self.file = open_append(&file_for(&self.path, &period))?;
+            self.period = period;
+            self.remove_old_files();
+        }
+        Ok(())
+    }
+
+    fn remove_old_files(&self) {
+        if self.max_files == 0 || self.rotation == LogRotation::Never {
+            return;
+        }
+        let (Some(directory), Some(name)) = (self.path.parent(), self.path.file_name()) else {
+            return;
+        };
+        let directory = if directory.as_os_str().is_empty() { PathBuf::from(".") } else { directory.to_path_buf() };
+        let prefix = format!("{}.", name.to_string_lossy());
+
+        let Ok(entries) = fs::read_dir(&directory) else {
+            return;
+        };
+        // Only files this rotation wrote count; period suffixes sort chronologically
+        let mut rotated: Vec<PathBuf> = entries
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| {
+                let file_name = entry.file_name().to_string_lossy().into_owned();
+                file_name.strip_prefix(&prefix).is_some_and(|suffix| is_period(self.rotation, suffix))
+            })
+            .map(|entry| entry.path())
+            .collect();
+        rotated.sort();
+
+        let excess = rotated.len().saturating_sub(self.max_files);
+        for old in &rotated[..excess] {
+            // A failed delete is retried at the next rotation; logging it here would recurse
+            let _ = fs::remove_file(old);
+        }
+    }
+}
+
+impl Write for RollingFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.roll()?;
+        self.file.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.file.flush()
+    }
+}
+
+fn period(rotation: LogRotation) -> String {
+    match rotation {
+        LogRotation::Hourly => Utc::now().format("%Y-%m-%d-%H").to_string(),
+        LogRotation::Daily => Utc::now().format("%Y-%m-%d").to_string(),
+        LogRotation::Never => String::new(),
+    }
+}
+
+// Whether `suffix` is exactly a period name `period` produces for `rotation`
+fn is_period(rotation: LogRotation, suffix: &str) -> bool {
+    let is_date = |date: &str| NaiveDate::parse_from_str(date, "%Y-%m-%d").is_ok_and(|d| d.format("%Y-%m-%d").to_string() == date);
+    match rotation {
+        LogRotation::Daily => is_date(suffix),
+        LogRotation::Hourly => suffix.rsplit_once('-').is_some_and(|(date, hour)| {
+            is_date(date) && hour.len() == 2 && hour.parse::<u32>().is_ok_and(|hour| hour < 24)
+        }),
+        LogRotation::Never => false,
+    }
+}
+
+fn file_for(path: &Path, period: &str) -> PathBuf {
+    if period.is_empty() {
+        path.to_path_buf()
+    } else {
+        PathBuf::from(format!("{}.{}", path.display(), period))
+    }
+}
+
+fn open_append(path: &Path) -> io::Result<File> {
+    OpenOptions::new().create(true).append(true).open(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use tracing::info;
+
+    fn temp_log_dir(name: &str) -> PathBuf {
+        let directory = std::env::temp_dir().join(format!("arbitrage-logs-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&directory);
+        fs::create_dir_all(&directory).unwrap();
+        directory
+    }
+
+    fn file_names(directory: &Path) -> Vec<String> {
+        let mut names: Vec<String> = fs::read_dir(directory)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+            .collect();
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn period_suffixes_must_match_exactly() {
+        assert!(is_period(LogRotation::Daily, "2026-10-18"));
+        assert!(is_period(LogRotation::Hourly, "2026-10-18-07"));
+        assert!(!is_period(LogRotation::Daily, "2026-10-18-07"));
+        assert!(!is_period(LogRotation::Hourly, "2026-10-18"));
+        assert!(!is_period(LogRotation::Hourly, "2026-10-18-24"));
+        assert!(!is_period(LogRotation::Daily, "2026-10-18.gz"));
+        assert!(!is_period(LogRotation::Daily, "2026-1-8"));
+        assert!(!is_period(LogRotation::Daily, "bak"));
+    }
+
+    #[test]
+    fn retention_keeps_the_newest_rotated_files_only() {
+        let directory = temp_log_dir("retention");
+        for name in [
+            "bot.log.2026-10-14",
+            "bot.log.2026-10-15",
+            "bot.log.2026-10-16",
+            "bot.log.2026-10-16.gz",
+            "bot.log.bak",
+            "bot.log.old.2026-10-01",
+            "bot.log",
+            "other.log.2026-10-01",
+        ] {
+            fs::write(directory.join(name), "").unwrap();
+        }
+
+        let config = LogFileConfig {
+            path: directory.join("bot.log").display().to_string(),
+            rotation: LogRotation::Daily,
+            max_files: 2,
+        };
+        RollingFile::open(&config).unwrap();
+
+        let today = format!("bot.log.{}", period(LogRotation::Daily));
+        let mut expected = vec![
+            "bot.log",
+            "bot.log.2026-10-16",
+            "bot.log.2026-10-16.gz",
+            "bot.log.bak",
+            "bot.log.old.2026-10-01",
+            "other.log.2026-10-01",
+            &today,
+        ];
+        expected.sort();
+        assert_eq!(file_names(&directory), expected);
+    }
+
+    #[test]
+    fn writes_roll_over_into_the_new_period() {
+        let directory = temp_log_dir("rollover");
+        let config = LogFileConfig {
+            path: directory.join("bot.log").display().to_string(),
+            rotation: LogRotation::Hourly,
+            max_files: 2,
+        };
+        let mut rolling = RollingFile::open(&config).unwrap();
+
+        // Pretend the file was opened in an earlier hour
+        rolling.period = "2026-01-01-00".to_string();
+        rolling.file = open_append(&directory.join("bot.log.2026-01-01-00")).unwrap();
+        writeln!(rolling, "first").unwrap();
+
+        let current = directory.join(format!("bot.log.{}", period(LogRotation::Hourly)));
+        assert_eq!(fs::read_to_string(&current).unwrap(), "first\n");
+        assert_eq!(fs::read_to_string(directory.join("bot.log.2026-01-01-00")).unwrap(), "");
+        assert_eq!(file_names(&directory).len(), 2);
+    }
+
+    #[derive(Clone, Default)]
+    struct Captured(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for Captured {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn json_lines_carry_event_fields_at_the_top_level() {
+        let captured = Captured::default();
+        let writer = captured.clone();
+        let subscriber = tracing_subscriber::registry()
+            .with(fmt::layer().with_ansi(false).with_writer(move || writer.clone()).event_format(JsonFormat));
+
+        tracing::subscriber::with_default(subscriber, || {
+            let span = tracing::info_span!("cycle");
+            let _entered = span.enter();
+            info!(token_pair = "WETH/USDC", profit_usd = 12.5, block = 50_000_000u64, delta = -3i64, executed = false, "Arbitrage opportunity found");
+        });
+
+        let output = String::from_utf8(captured.0.lock().unwrap().clone()).unwrap();
+        assert_eq!(output.lines().count(), 1);
+        let line: Value = serde_json::from_str(output.trim_end()).unwrap();
+
+        assert_eq!(line["level"], "INFO");
+        assert_eq!(line["target"], module_path!());
+        assert_eq!(line["message"], "Arbitrage opportunity found");
+        assert_eq!(line["spans"], serde_json::json!(["cycle"]));
+        assert_eq!(line["token_pair"], "WETH/USDC");
+        assert_eq!(line["profit_usd"], 12.5);
+        assert_eq!(line["block"], 50_000_000);
+        assert_eq!(line["delta"], -3);
+        assert_eq!(line["executed"], false);
+        assert!(chrono::DateTime::parse_from_rfc3339(line["timestamp"].as_str().unwrap()).is_ok());
+    }
+}
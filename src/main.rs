@@ -1,74 +1,670 @@
-use anyhow::Result;
-use clap::Parser;
+use anyhow::{bail, Result};
+use chrono::{DateTime, Utc};
+use clap::{Parser, Subcommand};
+use rust_decimal::Decimal;
 use std::time::Duration;
 use tokio::time;
 use tracing::{info, error, warn};
 
+mod approvals;
 mod config;
 mod database;
 mod dex;
 mod arbitrage;
+mod backtest;
 mod errors;
+mod export;
+mod health;
+mod lifecycle;
+mod logging;
+mod metrics;
+mod pnl;
+mod reload;
+mod report;
+mod retention;
+mod risk;
+mod rpc;
+mod server;
+mod submission;
+mod validation;
+mod wallet;
 
-use config::Config;
-use database::Database;
-use arbitrage::ArbitrageDetector;
+use config::{Config, RpcMode};
+use database::{ArbitrageOpportunity, Database, OpportunityOrder, OpportunityQuery};
+use arbitrage::{token_pairs, ArbitrageDetector};
+use backtest::PoolStateSource;
+use dex::{DexManager, TokenPair};
+use approvals::ApprovalManager;
+use pnl::PnlAccountant;
+use report::ReportFormat;
+use export::{ExportFormat, ExportTable};
+use health::HealthChecks;
+use risk::RiskManager;
 
 #[derive(Parser)]
 #[command(name = "polygon-arbitrage-bot")]
 #[command(about = "A Polygon arbitrage opportunity detector bot")]
 struct Args {
-    #[arg(short, long, default_value = "config.toml")]
+    #[arg(short, long, global = true, default_value = "config.toml")]
     config: String,
     
-    #[arg(short, long)]
+    #[arg(short, long, global = true, help = "Never sign or submit transactions")]
     dry_run: bool,
+    
+    #[arg(long, global = true, help = "Override polygon.rpc_url")]
+    rpc_url: Option<String>,
+    
+    #[arg(long, global = true, help = "Override database.url")]
+    database_url: Option<String>,
+    
+    #[arg(long = "set", global = true, value_name = "KEY=VALUE", value_parser = parse_override, help = "Override any configuration key, e.g. --set arbitrage.min_profit_usd=5")]
+    overrides: Vec<(String, String)>,
+    
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    #[command(about = "Run the detection loop (the default when no subcommand is given)")]
+    Run,
+    
+    #[command(about = "Run a single detection cycle and exit, e.g. from cron")]
+    Once {
+        #[arg(long, help = "Print the opportunities found as JSON")]
+        json: bool,
+    },
+    
+    #[command(about = "Print the current quote from every enabled DEX for a token pair")]
+    Quote {
+        #[arg(help = "Token pair, e.g. WETH/USDC")]
+        pair: String,
+    },
+    
+    #[command(about = "Report missing or insufficient token approvals for DEX routers")]
+    Approvals {
+        #[arg(long, help = "Submit approval transactions for insufficient allowances")]
+        submit: bool,
+    },
+    
+    #[command(about = "Record realised PnL for an executed opportunity from its transaction receipts")]
+    RecordExecution {
+        #[arg(long)]
+        opportunity_id: i64,
+        
+        #[arg(long = "tx", required = true, help = "Transaction hash of the execution (repeat for bundles)")]
+        tx_hashes: Vec<String>,
+    },
+    
+    #[command(about = "Compare estimated and realised profit per DEX route")]
+    PnlReport,
+    
+    #[command(about = "Replay detection over a historical block range")]
+    Backtest {
+        #[arg(long)]
+        from_block: u64,
+        
+        #[arg(long)]
+        to_block: u64,
+        
+        #[arg(long, default_value_t = 1, help = "Evaluate every Nth block (archive mode only)")]
+        step: u64,
+        
+        #[arg(long, help = "JSON file of recorded reserve snapshots instead of archive eth_call")]
+        snapshots: Option<String>,
+        
+        #[arg(long, conflicts_with = "snapshots", help = "Use quotes stored in the price_snapshots table")]
+        price_history: bool,
+        
+        #[arg(long, help = "Print the full report as JSON")]
+        json: bool,
+    },
+    
+    #[command(about = "Summarise opportunities per pair, DEX route and hour of day")]
+    Report {
+        #[arg(long, default_value_t = 24, help = "How many hours back to look")]
+        hours: i64,
+        
+        #[arg(long, default_value = "table", help = "table, json or csv")]
+        format: ReportFormat,
+    },
+    
+    #[command(about = "Export opportunities or price history to CSV, JSON Lines or Parquet")]
+    Export {
+        #[arg(long, default_value = "opportunities", help = "opportunities or prices")]
+        table: ExportTable,
+        
+        #[arg(long, default_value = "csv", help = "csv, jsonl or parquet")]
+        format: ExportFormat,
+        
+        #[arg(short, long, help = "File to write")]
+        output: String,
+        
+        #[arg(long, help = "Token pair, e.g. WETH/USDC")]
+        pair: Option<String>,
+        
+        #[arg(long, help = "Only quotes from this DEX (prices)")]
+        dex: Option<String>,
+        
+        #[arg(long, help = "Only opportunities bought on this DEX")]
+        buy_dex: Option<String>,
+        
+        #[arg(long, help = "Only opportunities sold on this DEX")]
+        sell_dex: Option<String>,
+        
+        #[arg(long, help = "Only rows at or after this RFC 3339 time")]
+        since: Option<DateTime<Utc>>,
+        
+        #[arg(long, help = "Only rows at or before this RFC 3339 time")]
+        until: Option<DateTime<Utc>>,
+        
+        #[arg(long, help = "Minimum estimated profit in USD (opportunities)")]
+        min_profit: Option<Decimal>,
+    },
+    
+    #[command(about = "Show the risk kill switch status")]
+    Risk {
+        #[arg(long, help = "Clear a tripped kill switch and re-enable execution")]
+        reset: bool,
+    },
+    
+    #[command(subcommand, about = "Inspect and maintain the database")]
+    Db(DbCommand),
+    
+    #[command(subcommand, about = "Check the configuration file")]
+    Config(ConfigCommand),
+}
+
+#[derive(Subcommand)]
+enum DbCommand {
+    #[command(about = "Apply pending database migrations")]
+    Migrate {
+        #[arg(long, help = "List applied and pending migrations without applying any")]
+        status: bool,
+    },
+    
+    #[command(about = "Search recorded opportunities")]
+    Opportunities {
+        #[arg(long, help = "Token pair, e.g. WETH/USDC")]
+        pair: Option<String>,
+        
+        #[arg(long)]
+        buy_dex: Option<String>,
+        
+        #[arg(long)]
+        sell_dex: Option<String>,
+        
+        #[arg(long, help = "Only opportunities at or after this RFC 3339 time")]
+        since: Option<DateTime<Utc>>,
+        
+        #[arg(long, help = "Only opportunities at or before this RFC 3339 time")]
+        until: Option<DateTime<Utc>>,
+        
+        #[arg(long, help = "Minimum estimated profit in USD")]
+        min_profit: Option<Decimal>,
+        
+        #[arg(long, conflicts_with = "not_executed", help = "Only opportunities with a recorded execution")]
+        executed: bool,
+        
+        #[arg(long, help = "Only opportunities that were never executed")]
+        not_executed: bool,
+        
+        #[arg(long, default_value = "newest", help = "newest, oldest or most-profitable")]
+        order: OpportunityOrder,
+        
+        #[arg(long, default_value_t = 10)]
+        limit: i64,
+        
+        #[arg(long, default_value_t = 0)]
+        offset: i64,
+    },
+    
+    #[command(about = "Show the recorded price series for a token pair")]
+    Prices {
+        #[arg(help = "Token pair, e.g. WETH/USDC")]
+        pair: String,
+        
+        #[arg(long, help = "Only show quotes from this DEX")]
+        dex: Option<String>,
+        
+        #[arg(long, default_value_t = 24, help = "How many hours back to look")]
+        hours: i64,
+    },
+    
+    #[command(about = "Apply the [database.retention] policy now")]
+    Prune,
+    
+    #[command(about = "Reclaim space freed by pruning (VACUUM, plus a WAL checkpoint on SQLite)")]
+    Vacuum,
+}
+
+#[derive(Subcommand)]
+enum ConfigCommand {
+    #[command(about = "Check the configuration file, and the chain it points at, without starting the bot")]
+    Validate {
+        #[arg(long, help = "Skip the checks that need the RPC endpoint")]
+        offline: bool,
+    },
+}
+
+fn parse_override(raw: &str) -> std::result::Result<(String, String), String> {
+    match raw.split_once('=') {
+        Some((key, value)) if !key.trim().is_empty() => Ok((key.trim().to_string(), value.to_string())),
+        _ => Err(format!("expected KEY=VALUE, got {:?}", raw)),
+    }
 }
 
 #[tokio::main]
 async fn main() -> Result<()> {
-    // Initialize logging
-    tracing_subscriber::fmt::init();
-    
     let args = Args::parse();
     
     // Load configuration
-    let config = Config::load(&args.config)?;
+    let mut overrides = args.overrides.clone();
+    if let Some(rpc_url) = &args.rpc_url {
+        overrides.push(("polygon.rpc_url".to_string(), rpc_url.clone()));
+    }
+    if let Some(database_url) = &args.database_url {
+        overrides.push(("database.url".to_string(), database_url.clone()));
+    }
+    let config = Config::load(&args.config, &overrides)?;
+    let logging = logging::init(&config.general)?;
     info!("Configuration loaded from {}", args.config);
     
+    // Commands that trade or check the config get the static checks; only the
+    // ones that talk to the chain pay for the RPC round trips. Reports, exports
+    // and database maintenance run whatever the rest of the file looks like.
+    let (validate, on_chain) = match &args.command {
+        None | Some(Command::Run) | Some(Command::Once { .. }) => (true, true),
+        Some(Command::Approvals { .. }) => (true, false),
+        Some(Command::Config(ConfigCommand::Validate { offline })) => (true, !*offline),
+        _ => (false, false),
+    };
+    if validate {
+        let mut report = validation::validate(&config);
+        if on_chain {
+            validation::validate_on_chain(&config, &mut report).await;
+        }
+        report.into_result()?;
+    }
+    
+    match &args.command {
+        Some(Command::Config(ConfigCommand::Validate { .. })) => {
+            println!("{} is valid", args.config);
+            return Ok(());
+        }
+        Some(Command::Quote { pair }) => {
+            return run_quote(&config, pair).await;
+        }
+        Some(Command::Approvals { submit }) => {
+            return run_approvals(&config, *submit && !args.dry_run).await;
+        }
+        Some(Command::Backtest { from_block, to_block, step, snapshots, price_history, json }) => {
+            let source = if *price_history {
+                let database = Database::new(&config.database.url).await?;
+                database.migrate().await?;
+                PoolStateSource::PriceHistory(database.get_price_snapshots_in_blocks(*from_block, *to_block).await?)
+            } else if let Some(path) = snapshots {
+                PoolStateSource::load_snapshots(path)?
+            } else {
+                PoolStateSource::Archive(DexManager::new(config.clone()).await?)
+            };
+            return run_backtest(&config, &source, *from_block, *to_block, *step, *json).await;
+        }
+        Some(Command::Db(DbCommand::Migrate { status })) => {
+            let database = Database::new(&config.database.url).await?;
+            if !*status {
+                let applied = database.migrate().await?;
+                println!("Applied {} migration(s)", applied);
+            }
+            for migration in database.migration_status().await? {
+                match migration.applied_at {
+                    Some(applied_at) => println!("{:04}_{} | applied {}", migration.version, migration.name, applied_at),
+                    None => println!("{:04}_{} | pending", migration.version, migration.name),
+                }
+            }
+            return Ok(());
+        }
+        _ => {}
+    }
+    
     // Initialize database
     let database = Database::new(&config.database.url).await?;
     database.migrate().await?;
-    info!("Database initialized and migrated");
+    info!("Database initialized and migrated ({})", database.backend());
+    
+    match args.command {
+        Some(Command::RecordExecution { opportunity_id, tx_hashes }) => {
+            let tx_hashes = tx_hashes
+                .iter()
+                .map(|hash| hash.parse())
+                .collect::<std::result::Result<Vec<_>, _>>()?;
+            PnlAccountant::new(&config)?
+                .record_execution(&database, opportunity_id, &tx_hashes)
+                .await?;
+            RiskManager::new(config.risk.clone()).post_trade_check(&database).await?;
+            return Ok(());
+        }
+        Some(Command::PnlReport) => {
+            for route in database.get_execution_report().await? {
+                println!(
+                    "{} {} -> {} | executions: {} (reverted: {}) | estimated: ${:.2} | realised: ${:.2} | difference: ${:.2}",
+                    route.token_pair,
+                    route.buy_dex,
+                    route.sell_dex,
+                    route.executions,
+                    route.reverted,
+                    route.estimated_profit_usd,
+                    route.realised_pnl_usd,
+                    route.realised_pnl_usd - route.estimated_profit_usd
+                );
+            }
+            return Ok(());
+        }
+        Some(Command::Db(DbCommand::Opportunities {
+            pair, buy_dex, sell_dex, since, until, min_profit, executed, not_executed, order, limit, offset,
+        })) => {
+            let mut query = OpportunityQuery::new().order(order).limit(limit).offset(offset);
+            if let Some(pair) = pair {
+                query = query.token_pair(pair);
+            }
+            if let Some(dex) = buy_dex {
+                query = query.buy_dex(dex);
+            }
+            if let Some(dex) = sell_dex {
+                query = query.sell_dex(dex);
+            }
+            if let Some(since) = since {
+                query = query.since(since);
+            }
+            if let Some(until) = until {
+                query = query.until(until);
+            }
+            if let Some(profit) = min_profit {
+                query = query.min_profit_usd(profit);
+            }
+            if executed || not_executed {
+                query = query.executed(executed);
+            }
+            
+            for opportunity in database.query_opportunities(&query).await? {
+                println!(
+                    "#{} {} | {} | {} -> {} | buy: {} | sell: {} | profit: ${} ({}%)",
+                    opportunity.id.unwrap_or_default(),
+                    opportunity.timestamp,
+                    opportunity.token_pair,
+                    opportunity.buy_dex,
+                    opportunity.sell_dex,
+                    opportunity.buy_price,
+                    opportunity.sell_price,
+                    opportunity.profit_usd,
+                    opportunity.profit_percentage
+                );
+            }
+            return Ok(());
+        }
+        Some(Command::Report { hours, format }) => {
+            let to = chrono::Utc::now();
+            let from = to - chrono::Duration::hours(hours);
+            let report = report::build_report(&database, from, to).await?;
+            print!("{}", report.render(format)?);
+            return Ok(());
+        }
+        Some(Command::Export { table, format, output, pair, dex, buy_dex, sell_dex, since, until, min_profit }) => {
+            let rows = match table {
+                ExportTable::Opportunities => {
+                    let mut query = OpportunityQuery::new().order(OpportunityOrder::Oldest).limit(i64::MAX);
+                    if let Some(pair) = pair {
+                        query = query.token_pair(pair);
+                    }
+                    if let Some(dex) = buy_dex {
+                        query = query.buy_dex(dex);
+                    }
+                    if let Some(dex) = sell_dex {
+                        query = query.sell_dex(dex);
+                    }
+                    if let Some(since) = since {
+                        query = query.since(since);
+                    }
+                    if let Some(until) = until {
+                        query = query.until(until);
+                    }
+                    if let Some(profit) = min_profit {
+                        query = query.min_profit_usd(profit);
+                    }
+                    export::export_opportunities(&database, &query, format, &output).await?
+                }
+                ExportTable::Prices => {
+                    export::export_price_snapshots(&database, pair.as_deref(), dex.as_deref(), since, until, format, &output).await?
+                }
+            };
+            info!("Exported {} row(s) to {}", rows, output);
+            return Ok(());
+        }
+        Some(Command::Db(DbCommand::Prices { pair, dex, hours })) => {
+            let to = chrono::Utc::now();
+            let from = to - chrono::Duration::hours(hours);
+            for snapshot in database.get_price_series(&pair, dex.as_deref(), from, to).await? {
+                println!(
+                    "{} | {} | block {} | price: {} | liquidity: {} | {}ms",
+                    snapshot.timestamp,
+                    snapshot.dex_name,
+                    snapshot.block_number.map_or("-".to_string(), |block| block.to_string()),
+                    snapshot.price,
+                    snapshot.liquidity,
+                    snapshot.latency_ms
+                );
+            }
+            return Ok(());
+        }
+        Some(Command::Db(DbCommand::Prune)) => {
+            let deleted = retention::apply(&database, &config.database.retention, chrono::Utc::now()).await?;
+            println!("Pruned {} opportunities", deleted);
+            return Ok(());
+        }
+        Some(Command::Db(DbCommand::Vacuum)) => {
+            database.vacuum().await?;
+            println!("Vacuumed {} database", database.backend());
+            return Ok(());
+        }
+        Some(Command::Risk { reset }) => {
+            if reset {
+                RiskManager::new(config.risk.clone()).reset(&database).await?;
+            }
+            match database.get_kill_switch().await? {
+                Some((reason, tripped_at)) => println!("Kill switch ACTIVE since {}: {}", tripped_at, reason),
+                None => println!("Kill switch inactive, execution enabled"),
+            }
+            return Ok(());
+        }
+        Some(Command::Once { json }) => {
+            let mut detector = ArbitrageDetector::new(config.clone(), database).await?;
+            let opportunities = detector.check_arbitrage_opportunities().await?;
+            if json {
+                println!("{}", serde_json::to_string_pretty(&opportunities)?);
+            } else {
+                log_opportunities(&opportunities);
+            }
+            return Ok(());
+        }
+        _ => {}
+    }
+    
+    let retention = &config.database.retention;
+    if retention.max_age_days > 0 || retention.max_rows > 0 || retention.vacuum_interval_hours > 0 {
+        tokio::spawn(retention::run(database.clone(), retention.clone()));
+    }
+    
+    if config.server.enabled {
+        let chain_backed = config.dexes.all().iter().any(|dex| dex.enabled) || config.wallet.is_some();
+        // Probing a replayed cassette would consume the recorded responses
+        let provider = if chain_backed && config.polygon.rpc_mode != RpcMode::Replay {
+            Some(rpc::connect(&config.polygon)?)
+        } else {
+            None
+        };
+        let checks = HealthChecks::new(&config, database.clone(), provider);
+        tokio::spawn(server::bind(&config.server, checks)?);
+    }
     
     // Initialize arbitrage detector
     let mut detector = ArbitrageDetector::new(config.clone(), database).await?;
     info!("Arbitrage detector initialized");
     
+    if config.wallet.is_some() && config.approvals.check_on_startup {
+        let submit = config.approvals.submit_on_startup && !args.dry_run;
+        if let Err(e) = run_approvals(&config, submit).await {
+            warn!("Approval check failed: {}", e);
+        }
+    }
+    
     // Main detection loop
+    let mut config = config;
     let mut interval = time::interval(Duration::from_secs(config.general.check_interval));
+    let mut reloads = reload::watch(&args.config);
     
     loop {
-        interval.tick().await;
-        
-        match detector.check_arbitrage_opportunities().await {
-            Ok(opportunities) => {
-                if opportunities.is_empty() {
-                    info!("No arbitrage opportunities found");
-                } else {
-                    info!("Found {} arbitrage opportunities", opportunities.len());
-                    for opportunity in opportunities {
-                        info!("Opportunity: {} -> {} | Profit: ${:.2}", 
-                            opportunity.buy_dex, 
-                            opportunity.sell_dex, 
-                            opportunity.profit_usd
-                        );
+        tokio::select! {
+            _ = interval.tick() => {
+                match detector.check_arbitrage_opportunities().await {
+                    Ok(opportunities) => log_opportunities(&opportunities),
+                    Err(e) => {
+                        error!("Error checking arbitrage opportunities: {}", e);
                     }
                 }
             }
-            Err(e) => {
-                error!("Error checking arbitrage opportunities: {}", e);
+            Some(()) = reloads.recv() => {
+                let reloaded = match reload::reload(&config, &args.config, &overrides).await {
+                    Ok(reloaded) => reloaded,
+                    Err(e) => {
+                        error!("Rejected new configuration, keeping the running one: {}", e);
+                        continue;
+                    }
+                };
+                if reloaded.changed.is_empty() {
+                    info!("Configuration reloaded, nothing to apply");
+                    continue;
+                }
+                
+                if reloaded.changed.iter().any(|section| section == "general") {
+                    if let Err(e) = logging.apply(&reloaded.config.general) {
+                        error!("Rejected new configuration, keeping the running one: {}", e);
+                        continue;
+                    }
+                }
+                let rebuild_dexes = reloaded.changed.iter().any(|section| reload::DEX_SECTIONS.contains(&section.as_str()));
+                if let Err(e) = detector.apply_config(reloaded.config.clone(), rebuild_dexes).await {
+                    error!("Rejected new configuration, keeping the running one: {}", e);
+                    continue;
+                }
+                if reloaded.config.general.check_interval != config.general.check_interval {
+                    interval = time::interval(Duration::from_secs(reloaded.config.general.check_interval));
+                }
+                config = reloaded.config;
+                info!("Configuration reloaded, applied [{}]", reloaded.changed.join("], ["));
             }
         }
     }
-}
\ No newline at end of file
+}
+
+fn log_opportunities(opportunities: &[ArbitrageOpportunity]) {
+    if opportunities.is_empty() {
+        info!("No arbitrage opportunities found");
+    } else {
+        info!("Found {} arbitrage opportunities", opportunities.len());
+        for opportunity in opportunities {
+            info!(
+                token_pair = %opportunity.token_pair,
+                buy_dex = %opportunity.buy_dex,
+                sell_dex = %opportunity.sell_dex,
+                profit_usd = %opportunity.profit_usd.round_dp(2),
+                "Opportunity: {} -> {}", opportunity.buy_dex, opportunity.sell_dex
+            );
+        }
+    }
+}
+
+async fn run_quote(config: &Config, pair: &str) -> Result<()> {
+    let pairs = token_pairs(config);
+    let name = |token_pair: &TokenPair| format!("{}/{}", token_pair.token0.symbol, token_pair.token1.symbol);
+    let Some(token_pair) = pairs.iter().find(|token_pair| name(token_pair).eq_ignore_ascii_case(pair)) else {
+        let known: Vec<String> = pairs.iter().map(name).collect();
+        bail!("Unknown token pair {}; configured pairs: {}", pair, known.join(", "));
+    };
+    
+    let quotes = DexManager::new(config.clone()).await?.get_all_prices(token_pair).await;
+    if quotes.is_empty() {
+        bail!("No DEX returned a quote for {}", name(token_pair));
+    }
+    
+    for quote in &quotes {
+        println!(
+            "{} | price: {} | liquidity: {} | block {} | {}ms",
+            quote.dex_name,
+            quote.price,
+            quote.liquidity,
+            quote.block_number.map_or("-".to_string(), |block| block.to_string()),
+            quote.latency_ms
+        );
+    }
+    
+    let lowest = quotes.iter().map(|quote| quote.price).min().unwrap_or_default();
+    let highest = quotes.iter().map(|quote| quote.price).max().unwrap_or_default();
+    if !lowest.is_zero() {
+        println!("Spread: {} ({:.4}%)", highest - lowest, (highest - lowest) / lowest * Decimal::ONE_HUNDRED);
+    }
+    
+    Ok(())
+}
+
+async fn run_approvals(config: &Config, submit: bool) -> Result<()> {
+    let manager = ApprovalManager::new(config)?;
+    let statuses = manager.check().await?;
+    ApprovalManager::report(&statuses);
+    
+    if submit {
+        match manager.approve_missing(&statuses).await? {
+            Some(outcome) => info!(
+                "Submitted {} approval transaction(s) via {:?}: {:?} (bundle: {:?}, target block: {:?})",
+                outcome.tx_hashes.len(), outcome.backend, outcome.tx_hashes, outcome.bundle_hash, outcome.target_block
+            ),
+            None => info!("No approvals to submit"),
+        }
+    }
+    
+    Ok(())
+}
+
+async fn run_backtest(
+    config: &Config,
+    source: &PoolStateSource,
+    from_block: u64,
+    to_block: u64,
+    step: u64,
+    json: bool,
+) -> Result<()> {
+    let report = backtest::run_backtest(config, source, from_block, to_block, step).await?;
+    
+    if json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+        return Ok(());
+    }
+    
+    println!("Blocks {}..={} ({} evaluated)", report.from_block, report.to_block, report.blocks_evaluated);
+    for pair in &report.pairs {
+        println!(
+            "{} | opportunities: {} | theoretical PnL: ${:.2} | gas: ${:.2}",
+            pair.token_pair, pair.opportunities, pair.theoretical_pnl_usd, pair.gas_spent_usd
+        );
+    }
+    println!(
+        "Total | opportunities: {} | theoretical PnL: ${:.2} | gas: ${:.2}",
+        report.opportunities.len(),
+        report.theoretical_pnl_usd,
+        report.gas_spent_usd
+    );
+    for (reason, count) in &report.rejections {
+        println!("Rejected ({}): {}", reason, count);
+    }
+    
+    Ok(())
+}
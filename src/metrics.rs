@@ -0,0 +1,243 @@
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::sync::{LazyLock, Mutex};
+use std::time::Duration;
+
+// Process-wide so fetchers, the RPC transport and the detector can record
+// without threading a handle through every constructor
+pub static METRICS: LazyLock<Metrics> = LazyLock::new(Metrics::new);
+
+const LATENCY_BUCKETS: &[f64] = &[0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0];
+const SPREAD_BUCKETS: &[f64] = &[0.01, 0.05, 0.1, 0.25, 0.5, 1.0, 2.0, 5.0, 10.0];
+
+pub struct Metrics {
+    pub cycles: CounterVec,
+    pub cycle_duration: HistogramVec,
+    pub quotes: CounterVec,
+    pub rpc_latency: HistogramVec,
+    pub spreads: CounterVec,
+    pub spread_percent: HistogramVec,
+    pub opportunities: CounterVec,
+    pub estimated_profit: CounterVec,
+    pub last_block: GaugeVec,
+}
+
+impl Metrics {
+    fn new() -> Self {
+        Self {
+            cycles: CounterVec::new("arbitrage_cycles_total", "Detection cycles run", &["result"]),
+            cycle_duration: HistogramVec::new(
+                "arbitrage_cycle_duration_seconds",
+                "Time taken by one detection cycle",
+                &[],
+                LATENCY_BUCKETS,
+            ),
+            quotes: CounterVec::new("arbitrage_quotes_total", "Price quotes requested per DEX", &["dex", "result"]),
+            rpc_latency: HistogramVec::new(
+                "arbitrage_rpc_request_duration_seconds",
+                "JSON-RPC round trip time per method",
+                &["method", "result"],
+                LATENCY_BUCKETS,
+            ),
+            spreads: CounterVec::new(
+                "arbitrage_spreads_total",
+                "Cross-DEX spreads evaluated per pair, by outcome",
+                &["pair", "outcome"],
+            ),
+            spread_percent: HistogramVec::new(
+                "arbitrage_spread_percent",
+                "Best sell over best buy price, in percent",
+                &["pair"],
+                SPREAD_BUCKETS,
+            ),
+            opportunities: CounterVec::new("arbitrage_opportunities_total", "Opportunities found per pair", &["pair"]),
+            estimated_profit: CounterVec::new(
+                "arbitrage_estimated_profit_usd_total",
+                "Sum of estimated profit of the opportunities found",
+                &["pair"],
+            ),
+            last_block: GaugeVec::new("arbitrage_last_block", "Highest block number seen in price quotes", &[]),
+        }
+    }
+
+    pub fn render(&self) -> String {
+        let mut output = String::new();
+        self.cycles.render(&mut output);
+        self.cycle_duration.render(&mut output);
+        self.quotes.render(&mut output);
+        self.rpc_latency.render(&mut output);
+        self.spreads.render(&mut output);
+        self.spread_percent.render(&mut output);
+        self.opportunities.render(&mut output);
+        self.estimated_profit.render(&mut output);
+        self.last_block.render(&mut output);
+        output
+    }
+}
+
+pub struct CounterVec {
+    name: &'static str,
+    help: &'static str,
+    labels: &'static [&'static str],
+    values: Mutex<BTreeMap<Vec<String>, f64>>,
+}
+
+impl CounterVec {
+    fn new(name: &'static str, help: &'static str, labels: &'static [&'static str]) -> Self {
+        Self { name, help, labels, values: Mutex::new(BTreeMap::new()) }
+    }
+
+    pub fn inc(&self, labels: &[&str]) {
+        self.inc_by(labels, 1.0);
+    }
+
+    pub fn inc_by(&self, labels: &[&str], amount: f64) {
+        debug_assert_eq!(labels.len(), self.labels.len(), "{} label count", self.name);
+        // Counters only go up; a negative estimate would break rate()
+        if !amount.is_finite() || amount < 0.0 {
+            return;
+        }
+        *self.values.lock().unwrap().entry(owned(labels)).or_default() += amount;
+    }
+
+    fn render(&self, output: &mut String) {
+        header(output, self.name, self.help, "counter");
+        for (values, total) in self.values.lock().unwrap().iter() {
+            let _ = writeln!(output, "{}{} {}", self.name, label_set(self.labels, values, None), total);
+        }
+    }
+}
+
+pub struct GaugeVec {
+    name: &'static str,
+    help: &'static str,
+    labels: &'static [&'static str],
+    values: Mutex<BTreeMap<Vec<String>, f64>>,
+}
+
+impl GaugeVec {
+    fn new(name: &'static str, help: &'static str, labels: &'static [&'static str]) -> Self {
+        Self { name, help, labels, values: Mutex::new(BTreeMap::new()) }
+    }
+
+    pub fn set(&self, labels: &[&str], value: f64) {
+        self.values.lock().unwrap().insert(owned(labels), value);
+    }
+
+    fn render(&self, output: &mut String) {
+        header(output, self.name, self.help, "gauge");
+        for (values, value) in self.values.lock().unwrap().iter() {
+            let _ = writeln!(output, "{}{} {}", self.name, label_set(self.labels, values, None), value);
+        }
+    }
+}
+
+#[derive(Default)]
+struct Histogram {
+    buckets: Vec<u64>, // non-cumulative; summed when rendered
+    sum: f64,
+    count: u64,
+}
+
+pub struct HistogramVec {
+    name: &'static str,
+    help: &'static str,
+    labels: &'static [&'static str],
+    bounds: &'static [f64],
+    values: Mutex<BTreeMap<Vec<String>, Histogram>>,
+}
+
+impl HistogramVec {
+    fn new(name: &'static str, help: &'static str, labels: &'static [&'static str], bounds: &'static [f64]) -> Self {
+        Self { name, help, labels, bounds, values: Mutex::new(BTreeMap::new()) }
+    }
+
+    pub fn observe(&self, labels: &[&str], value: f64) {
+        debug_assert_eq!(labels.len(), self.labels.len(), "{} label count", self.name);
+        if !value.is_finite() {
+            return;
+        }
+        let mut values = self.values.lock().unwrap();
+        let histogram = values.entry(owned(labels)).or_default();
+        histogram.buckets.resize(self.bounds.len(), 0);
+        if let Some(index) = self.bounds.iter().position(|bound| value <= *bound) {
+            histogram.buckets[index] += 1;
+        }
+        histogram.sum += value;
+        histogram.count += 1;
+    }
+
+    pub fn observe_duration(&self, labels: &[&str], duration: Duration) {
+        self.observe(labels, duration.as_secs_f64());
+    }
+
+    fn render(&self, output: &mut String) {
+        header(output, self.name, self.help, "histogram");
+        for (values, histogram) in self.values.lock().unwrap().iter() {
+            let mut cumulative = 0;
+            for (bound, count) in self.bounds.iter().zip(&histogram.buckets) {
+                cumulative += count;
+                let le = bound.to_string();
+                let _ = writeln!(output, "{}_bucket{} {}", self.name, label_set(self.labels, values, Some(&le)), cumulative);
+            }
+            let _ = writeln!(output, "{}_bucket{} {}", self.name, label_set(self.labels, values, Some("+Inf")), histogram.count);
+            let _ = writeln!(output, "{}_sum{} {}", self.name, label_set(self.labels, values, None), histogram.sum);
+            let _ = writeln!(output, "{}_count{} {}", self.name, label_set(self.labels, values, None), histogram.count);
+        }
+    }
+}
+
+fn owned(labels: &[&str]) -> Vec<String> {
+    labels.iter().map(|label| label.to_string()).collect()
+}
+
+fn header(output: &mut String, name: &str, help: &str, kind: &str) {
+    let _ = writeln!(output, "# HELP {} {}", name, help);
+    let _ = writeln!(output, "# TYPE {} {}", name, kind);
+}
+
+fn label_set(names: &[&str], values: &[String], le: Option<&str>) -> String {
+    let mut pairs: Vec<String> = names
+        .iter()
+        .zip(values)
+        .map(|(name, value)| format!("{}=\"{}\"", name, escape(value)))
+        .collect();
+    if let Some(le) = le {
+        pairs.push(format!("le=\"{}\"", le));
+    }
+    if pairs.is_empty() {
+        String::new()
+    } else {
+        format!("{{{}}}", pairs.join(","))
+    }
+}
+
+fn escape(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn histograms_render_cumulative_buckets() {
+        let histogram = HistogramVec::new("test_seconds", "Test histogram", &["method"], &[0.1, 1.0]);
+        histogram.observe(&["eth_call"], 0.05);
+        histogram.observe(&["eth_call"], 0.5);
+        histogram.observe(&["eth_call"], 3.0);
+
+        let mut output = String::new();
+        histogram.render(&mut output);
+        assert_eq!(
+            output,
+            "# HELP test_seconds Test histogram\n\
+             # TYPE test_seconds histogram\n\
+             test_seconds_bucket{method=\"eth_call\",le=\"0.1\"} 1\n\
+             test_seconds_bucket{method=\"eth_call\",le=\"1\"} 2\n\
+             test_seconds_bucket{method=\"eth_call\",le=\"+Inf\"} 3\n\
+             test_seconds_sum{method=\"eth_call\"} 3.55\n\
+             test_seconds_count{method=\"eth_call\"} 3\n"
+        );
+    }
+}
@@ -0,0 +1,351 @@
+use crate::config::{Config, TokenConfig};
+use crate::database::{Database, Execution};
+use crate::dex::{get_uniswap_v2_factory_abi, to_decimal_units};
+use crate::errors::{ArbitrageError, Result};
+use crate::rpc::{self, RpcProvider};
+use chrono::Utc;
+use ethers::{
+    contract::Contract,
+    providers::Middleware,
+    types::{Address, TransactionReceipt, H256, U256},
+    utils::keccak256,
+};
+use rust_decimal::Decimal;
+use std::str::FromStr;
+use std::sync::Arc;
+use tracing::{info, warn};
+
+const SWAP_EVENT: &str = "Swap(address,uint256,uint256,uint256,uint256,address)";
+
+// Net movement of the two legs' tokens through the pair pools
+#[derive(Debug, Default)]
+struct TokenFlows {
+    quote_spent: Decimal,
+    quote_received: Decimal,
+    base_residual: Decimal,
+    swap_count: i64,
+}
+
+pub struct PnlAccountant {
+    provider: Arc<RpcProvider>,
+    config: Config,
+    wallet: Address,
+}
+
+impl PnlAccountant {
+    pub fn new(config: &Config) -> Result<Self> {
+        let wallet = config
+            .wallet
+            .as_ref()
+            .ok_or_else(|| ArbitrageError::Config("A [wallet] section is required for PnL accounting".to_string()))?;
+        let wallet = Address::from_str(&wallet.address)
+            .map_err(|e| ArbitrageError::Config(format!("Invalid wallet address: {}", e)))?;
+        let provider = rpc::connect(&config.polygon)?;
+
+        Ok(Self {
+            provider,
+            config: config.clone(),
+            wallet,
+        })
+    }
+
+    pub async fn record_execution(&self, database: &Database, opportunity_id: i64, tx_hashes: &[H256]) -> Result<Execution> {
+        let (token_pair, sell_price) = database
+            .get_opportunity_route(opportunity_id)
+            .await
+            .map_err(|e| ArbitrageError::Calculation(format!("Failed to load opportunity: {}", e)))?
+            .ok_or_else(|| ArbitrageError::Calculation(format!("Opportunity {} not found", opportunity_id)))?;
+        let (base, quote) = self.resolve_pair(&token_pair)?;
+        let pairs = self.pair_addresses(&base, &quote).await?;
+
+        let mut receipts = Vec::with_capacity(tx_hashes.len());
+        for hash in tx_hashes {
+            let receipt = self
+                .provider
+                .get_transaction_receipt(*hash)
+                .await?
+                .ok_or_else(|| ArbitrageError::Calculation(format!("No receipt for transaction {:?}", hash)))?;
+            receipts.push(receipt);
+        }
+
+        let flows = self.decode_flows(&receipts, &pairs, &base, &quote)?;
+        if flows.swap_count == 0 {
+            warn!("No Swap events found in execution of opportunity {}", opportunity_id);
+        }
+
+        let overflow = || ArbitrageError::Calculation("Gas cost overflows 256 bits".to_string());
+        let mut gas_used = U256::zero();
+        let mut gas_cost_wei = U256::zero();
+        for receipt in &receipts {
+            let used = receipt.gas_used.unwrap_or_default();
+            let cost = used.checked_mul(receipt.effective_gas_price.unwrap_or_default()).ok_or_else(overflow)?;
+            gas_used = gas_used.checked_add(used).ok_or_else(overflow)?;
+            gas_cost_wei = gas_cost_wei.checked_add(cost).ok_or_else(overflow)?;
+        }
+        let gas_used = i64::try_from(gas_used)
+            .map_err(|_| ArbitrageError::Calculation(format!("Gas used {} does not fit in i64", gas_used)))?;
+        let gas_cost_native = to_decimal_units(gas_cost_wei, 18)?;
+        let native_price = Decimal::try_from(self.config.polygon.native_price_usd)
+            .map_err(|e| ArbitrageError::Calculation(format!("Invalid native price: {}", e)))?;
+        let gas_cost_usd = gas_cost_native * native_price;
+
+        // The quote token is treated as USD, matching how opportunities are priced;
+        // leftover base tokens are marked at the opportunity's sell price
+        let realised_pnl_usd = flows.quote_received - flows.quote_spent
+            + flows.base_residual * sell_price
+            - gas_cost_usd;
+
+        let reverted = receipts.iter().any(|r| r.status != Some(1u64.into()));
+
+        let mut execution = Execution {
+            id: None,
+            opportunity_id,
+            timestamp: Utc::now(),
+            tx_hashes: tx_hashes.iter().map(|h| format!("{:?}", h)).collect(),
+            block_number: receipts
+                .iter()
+                .filter_map(|r| r.block_number)
+                .max()
+                .and_then(|b| i64::try_from(b.as_u64()).ok()),
+            status: if reverted { "reverted" } else { "success" }.to_string(),
+            quote_token: quote.symbol.clone(),
+            quote_spent: flows.quote_spent,
+            quote_received: flows.quote_received,
+            base_token: base.symbol.clone(),
+            base_residual: flows.base_residual,
+            swap_count: flows.swap_count,
+            gas_used,
+            gas_cost_native,
+            gas_cost_usd,
+            realised_pnl_usd,
+        };
+
+        execution.id = Some(
+            database
+                .save_execution(&execution)
+                .await
+                .map_err(|e| ArbitrageError::Calculation(format!("Failed to save execution: {}", e)))?,
+        );
+
+        info!(
+            "Recorded execution of opportunity {}: realised PnL ${:.2} ({})",
+            opportunity_id, execution.realised_pnl_usd, execution.status
+        );
+
+        Ok(execution)
+    }
+
+    fn resolve_pair(&self, token_pair: &str) -> Result<(TokenConfig, TokenConfig)> {
+        let invalid = || ArbitrageError::InvalidTokenPair {
+            from: token_pair.to_string(),
+            to: "configured tokens".to_string(),
+        };
+        let (base, quote) = token_pair.split_once('/').ok_or_else(invalid)?;
+        let find = |symbol: &str| {
+            self.config
+                .tokens
+                .all()
+                .into_iter()
+                .find(|token| token.symbol == symbol)
+                .cloned()
+        };
+
+        Ok((find(base).ok_or_else(invalid)?, find(quote).ok_or_else(invalid)?))
+    }
+
+    // The pool each enabled DEX's factory has deployed for the pair
+    async fn pair_addresses(&self, base: &TokenConfig, quote: &TokenConfig) -> Result<Vec<Address>> {
+        let (base_address, quote_address) = token_addresses(base, quote)?;
+        let mut pairs = Vec::new();
+        for dex in self.config.dexes.all().into_iter().filter(|dex| dex.enabled) {
+            let factory_address = Address::from_str(&dex.factory_address)
+                .map_err(|e| ArbitrageError::Config(format!("Invalid {} factory address: {}", dex.name, e)))?;
+            let factory = Contract::new(factory_address, get_uniswap_v2_factory_abi(), self.provider.clone());
+            let pair: Address = factory
+                .method::<_, Address>("getPair", (base_address, quote_address))?
+                .call()
+                .await?;
+            if !pair.is_zero() {
+                pairs.push(pair);
+            }
+        }
+
+        Ok(pairs)
+    }
+
+    fn decode_flows(&self, receipts: &[TransactionReceipt], pairs: &[Address], base: &TokenConfig, quote: &TokenConfig) -> Result<TokenFlows> {
+        let swap_topic = H256::from(keccak256(SWAP_EVENT));
+        let (base_address, quote_address) = token_addresses(base, quote)?;
+        // A V2 pair orders its tokens by address
+        let base_is_token0 = base_address < quote_address;
+
+        // Only Swap events emitted by the pair's own pools count, and only in the
+        // standard shape: sender and to indexed, then amount0In, amount1In,
+        // amount0Out and amount1Out as four data words. Pools of other pairs
+        // touched by the same transactions are skipped.
+        let mut flows = TokenFlows::default();
+        if let Some(receipt) = receipts.iter().find(|r| r.from != self.wallet) {
+            return Err(ArbitrageError::Calculation(format!(
+                "Transaction {:?} was sent by {:?}, not the wallet",
+                receipt.transaction_hash, receipt.from
+            )));
+        }
+        for log in receipts.iter().flat_map(|r| &r.logs) {
+            if log.topics.first() != Some(&swap_topic)
+                || log.topics.len() != 3
+                || log.data.len() != 128
+                || !pairs.contains(&log.address)
+            {
+                continue;
+            }
+
+            let word = |index: usize| U256::from_big_endian(&log.data[index * 32..(index + 1) * 32]);
+            let (base_in, quote_in, base_out, quote_out) = if base_is_token0 {
+                (word(0), word(1), word(2), word(3))
+            } else {
+                (word(1), word(0), word(3), word(2))
+            };
+
+            flows.quote_spent += to_decimal_units(quote_in, quote.decimals)?;
+            flows.quote_received += to_decimal_units(quote_out, quote.decimals)?;
+            flows.base_residual += to_decimal_units(base_out, base.decimals)? - to_decimal_units(base_in, base.decimals)?;
+            flows.swap_count += 1;
+        }
+
+        Ok(flows)
+    }
+}
+
+fn token_addresses(base: &TokenConfig, quote: &TokenConfig) -> Result<(Address, Address)> {
+    let base_address = Address::from_str(&base.address)
+        .map_err(|e| ArbitrageError::Config(format!("Invalid {} address: {}", base.symbol, e)))?;
+    let quote_address = Address::from_str(&quote.address)
+        .map_err(|e| ArbitrageError::Config(format!("Invalid {} address: {}", quote.symbol, e)))?;
+
+    Ok((base_address, quote_address))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::tests::test_config;
+    use crate::config::WalletConfig;
+    use ethers::types::{Bytes, Log};
+
+    const WALLET: &str = "0x00000000000000000000000000000000000000aa";
+    const BUY_POOL: &str = "0x00000000000000000000000000000000000000bb";
+    const SELL_POOL: &str = "0x00000000000000000000000000000000000000cc";
+    const OTHER_POOL: &str = "0x00000000000000000000000000000000000000dd";
+
+    fn accountant() -> PnlAccountant {
+        let mut config = test_config();
+        config.wallet = Some(WalletConfig {
+            address: WALLET.to_string(),
+            private_key: None,
+            min_native_balance: 1.0,
+            snapshot_interval: 60,
+        });
+        PnlAccountant::new(&config).unwrap()
+    }
+
+    // Amounts in event order: amount0In, amount1In, amount0Out, amount1Out
+    fn swap(pool: &str, amounts: [u128; 4]) -> Log {
+        let mut data = vec![0u8; 128];
+        for (index, amount) in amounts.into_iter().enumerate() {
+            U256::from(amount).to_big_endian(&mut data[index * 32..(index + 1) * 32]);
+        }
+        let wallet = H256::from(Address::from_str(WALLET).unwrap());
+        Log {
+            address: Address::from_str(pool).unwrap(),
+            topics: vec![H256::from(keccak256(SWAP_EVENT)), wallet, wallet],
+            data: Bytes::from(data),
+            ..Default::default()
+        }
+    }
+
+    fn receipt(logs: Vec<Log>) -> TransactionReceipt {
+        TransactionReceipt {
+            from: Address::from_str(WALLET).unwrap(),
+            logs,
+            ..Default::default()
+        }
+    }
+
+    fn decode(receipts: &[TransactionReceipt], base: &TokenConfig, quote: &TokenConfig) -> Result<TokenFlows> {
+        let pairs = [Address::from_str(BUY_POOL).unwrap(), Address::from_str(SELL_POOL).unwrap()];
+        accountant().decode_flows(receipts, &pairs, base, quote)
+    }
+
+    fn flows(logs: Vec<Log>) -> TokenFlows {
+        let config = test_config();
+        decode(&[receipt(logs)], &config.tokens.weth, &config.tokens.usdc).unwrap()
+    }
+
+    #[test]
+    fn swap_amounts_net_into_spent_received_and_residual() {
+        // USDC sorts below WETH, so it is token0 of the pair
+        let flows = flows(vec![
+            swap(BUY_POOL, [1_000_000_000, 0, 0, 550_000_000_000_000_000]),
+            swap(SELL_POOL, [0, 500_000_000_000_000_000, 1_004_250_000, 0]),
+        ]);
+
+        assert_eq!(flows.quote_spent, Decimal::from(1000));
+        assert_eq!(flows.quote_received, Decimal::from_str("1004.25").unwrap());
+        assert_eq!(flows.base_residual, Decimal::from_str("0.05").unwrap());
+        assert_eq!(flows.swap_count, 2);
+    }
+
+    #[test]
+    fn amounts_follow_the_pair_token_order() {
+        // WBTC sorts below USDC, so here the base token is token0
+        let config = test_config();
+        let logs = vec![
+            swap(BUY_POOL, [0, 1_000_000_000, 1_500_000, 0]),
+            swap(SELL_POOL, [1_500_000, 0, 0, 1_002_000_000]),
+        ];
+        let flows = decode(&[receipt(logs)], &config.tokens.wbtc, &config.tokens.usdc).unwrap();
+
+        assert_eq!(flows.quote_spent, Decimal::from(1000));
+        assert_eq!(flows.quote_received, Decimal::from(1002));
+        assert_eq!(flows.base_residual, Decimal::ZERO);
+    }
+
+    #[test]
+    fn swaps_outside_the_pair_pools_are_ignored() {
+        let flows = flows(vec![
+            swap(OTHER_POOL, [1_000_000_000, 0, 0, 550_000_000_000_000_000]),
+            swap(BUY_POOL, [2_000_000, 0, 0, 1_000_000_000_000_000]),
+        ]);
+
+        assert_eq!(flows.quote_spent, Decimal::from(2));
+        assert_eq!(flows.base_residual, Decimal::from_str("0.001").unwrap());
+        assert_eq!(flows.swap_count, 1);
+    }
+
+    #[test]
+    fn malformed_swaps_are_skipped() {
+        let mut short_data = swap(BUY_POOL, [1_000_000, 0, 0, 1]);
+        short_data.data = Bytes::from(vec![0x01; 96]);
+        let mut anonymous = swap(BUY_POOL, [1_000_000, 0, 0, 1]);
+        anonymous.topics.truncate(1);
+        let mut extra_topic = swap(BUY_POOL, [1_000_000, 0, 0, 1]);
+        extra_topic.topics.push(H256::from_low_u64_be(7));
+        let mut other_event = swap(BUY_POOL, [1_000_000, 0, 0, 1]);
+        other_event.topics[0] = H256::from(keccak256("Sync(uint112,uint112)"));
+
+        let flows = flows(vec![short_data, anonymous, extra_topic, other_event, swap(SELL_POOL, [0, 0, 2_000_000, 0])]);
+
+        assert_eq!(flows.quote_spent, Decimal::ZERO);
+        assert_eq!(flows.quote_received, Decimal::from(2));
+        assert_eq!(flows.swap_count, 1);
+    }
+
+    #[test]
+    fn transactions_from_other_senders_are_rejected() {
+        let config = test_config();
+        let mut foreign = receipt(vec![swap(BUY_POOL, [1_000_000, 0, 0, 1])]);
+        foreign.from = Address::from_str(OTHER_POOL).unwrap();
+
+        assert!(decode(&[receipt(Vec::new()), foreign], &config.tokens.weth, &config.tokens.usdc).is_err());
+    }
+}
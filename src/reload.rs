@@ -0,0 +1,202 @@
+use crate::config::Config;
+use crate::validation;
+use anyhow::Result;
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+use tokio::sync::mpsc;
+use tokio::time;
+use tracing::{info, warn};
+
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+// Sections the running bot holds connections or signing state for; edits to
+// them are reported and left for the next restart
+const RESTART_ONLY: &[&str] = &["polygon", "database", "submission", "wallet", "approvals", "server", "health"];
+
+// Sections that feed DexManager's fetchers, which are rebuilt when they change
+pub const DEX_SECTIONS: &[&str] = &["dexes", "synthetic_dexes", "tokens"];
+
+pub struct Reloaded {
+    pub config: Config,
+    pub changed: Vec<String>, // top-level sections that now differ
+}
+
+// Sends a message whenever the file's modification time changes or the
+// process receives SIGHUP; bursts collapse into one pending reload
+pub fn watch(path: &str) -> mpsc::Receiver<()> {
+    let (sender, receiver) = mpsc::channel(1);
+    let path = PathBuf::from(path);
+
+    tokio::spawn(async move {
+        let mut modified = modified_at(&path);
+        let mut poll = time::interval(POLL_INTERVAL);
+        let mut hangup = hangup_signal();
+
+        loop {
+            tokio::select! {
+                _ = poll.tick() => {
+                    let current = modified_at(&path);
+                    if current == modified {
+                        continue;
+                    }
+                    modified = current;
+                    info!("{} changed, reloading configuration", path.display());
+                }
+                _ = hangup.recv() => info!("SIGHUP received, reloading configuration"),
+            }
+
+            if sender.is_closed() {
+                return;
+            }
+            let _ = sender.try_send(());
+        }
+    });
+
+    receiver
+}
+
+fn modified_at(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|metadata| metadata.modified()).ok()
+}
+
+#[cfg(unix)]
+struct Hangup(Option<tokio::signal::unix::Signal>);
+
+#[cfg(unix)]
+fn hangup_signal() -> Hangup {
+    use tokio::signal::unix::{signal, SignalKind};
+    match signal(SignalKind::hangup()) {
+        Ok(signal) => Hangup(Some(signal)),
+        Err(e) => {
+            warn!("Cannot listen for SIGHUP, only file changes will reload: {}", e);
+            Hangup(None)
+        }
+    }
+}
+
+#[cfg(unix)]
+impl Hangup {
+    async fn recv(&mut self) {
+        match &mut self.0 {
+            Some(signal) => {
+                signal.recv().await;
+            }
+            None => std::future::pending().await,
+        }
+    }
+}
+
+#[cfg(not(unix))]
+struct Hangup;
+
+#[cfg(not(unix))]
+fn hangup_signal() -> Hangup {
+    Hangup
+}
+
+#[cfg(not(unix))]
+impl Hangup {
+    async fn recv(&mut self) {
+        std::future::pending().await
+    }
+}
+
+// Loads and fully validates the new file; any error leaves `current` in charge.
+// Restart-only sections keep their running values.
+pub async fn reload(current: &Config, path: &str, overrides: &[(String, String)]) -> Result<Reloaded> {
+    let mut config = Config::load(path, overrides)?;
+
+    let mut report = validation::validate(&config);
+    validation::validate_on_chain(&config, &mut report).await;
+    report.into_result()?;
+
+    let mut changed = changed_sections(current, &config)?;
+    keep_restart_only_sections(current, &mut config, &mut changed);
+
+    Ok(Reloaded { config, changed })
+}
+
+fn keep_restart_only_sections(current: &Config, config: &mut Config, changed: &mut Vec<String>) {
+    let pending: Vec<&String> = changed.iter().filter(|section| RESTART_ONLY.contains(&section.as_str())).collect();
+    if pending.is_empty() {
+        return;
+    }
+
+    warn!(
+        "Changes to [{}] take effect after a restart",
+        pending.iter().map(|section| section.as_str()).collect::<Vec<_>>().join("], [")
+    );
+    config.polygon = current.polygon.clone();
+    config.database = current.database.clone();
+    config.submission = current.submission.clone();
+    config.wallet = current.wallet.clone();
+    config.approvals = current.approvals.clone();
+    config.server = current.server.clone();
+    config.health = current.health.clone();
+    changed.retain(|section| !RESTART_ONLY.contains(&section.as_str()));
+}
+
+fn changed_sections(current: &Config, new: &Config) -> Result<Vec<String>> {
+    let (Value::Object(current), Value::Object(new)) = (serde_json::to_value(current)?, serde_json::to_value(new)?) else {
+        unreachable!("Config serializes to an object");
+    };
+
+    let mut changed: Vec<String> = new
+        .iter()
+        .filter(|(section, value)| current.get(*section) != Some(*value))
+        .map(|(section, _)| section.clone())
+        .collect();
+    changed.sort();
+    Ok(changed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::tests::test_config;
+    use crate::config::{PriceScript, SyntheticDexConfig};
+    use std::collections::HashMap;
+
+    #[test]
+    fn changed_sections_lists_each_differing_top_level_section() {
+        let current = test_config();
+        assert!(changed_sections(&current, &current.clone()).unwrap().is_empty());
+
+        let mut new = current.clone();
+        new.risk.max_notional_usd = 5_000.0;
+        new.arbitrage.min_profit_usd = 2.5;
+        new.tokens.usdc.decimals = 18;
+        new.synthetic_dexes.push(SyntheticDexConfig {
+            name: "Synthetic".to_string(),
+            enabled: true,
+            liquidity: 1_000_000.0,
+            pairs: HashMap::from([("WETH/USDC".to_string(), PriceScript::Static { price: 1800.0 })]),
+        });
+
+        assert_eq!(changed_sections(&current, &new).unwrap(), vec!["arbitrage", "risk", "synthetic_dexes", "tokens"]);
+    }
+
+    #[test]
+    fn restart_only_sections_keep_their_running_values() {
+        let current = test_config();
+        let mut config = current.clone();
+        config.polygon.rpc_url = "http://10.0.0.1:8545".to_string();
+        config.database.url = "sqlite:other.db".to_string();
+        config.approvals.submit_on_startup = true;
+        config.server.enabled = !current.server.enabled;
+        config.risk.max_trades_per_hour = 5;
+
+        let mut changed = changed_sections(&current, &config).unwrap();
+        assert_eq!(changed, vec!["approvals", "database", "polygon", "risk", "server"]);
+        keep_restart_only_sections(&current, &mut config, &mut changed);
+
+        assert_eq!(changed, vec!["risk"]);
+        assert_eq!(config.polygon.rpc_url, current.polygon.rpc_url);
+        assert_eq!(config.database.url, current.database.url);
+        assert!(!config.approvals.submit_on_startup);
+        assert_eq!(config.server.enabled, current.server.enabled);
+        assert_eq!(config.risk.max_trades_per_hour, 5);
+        assert_eq!(changed_sections(&current, &config).unwrap(), vec!["risk"]);
+    }
+}
@@ -0,0 +1,253 @@
+use crate::database::{Database, RouteProfits};
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use serde::Serialize;
+use std::fmt::Write;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReportFormat {
+    Table,
+    Json,
+    Csv,
+}
+
+impl std::str::FromStr for ReportFormat {
+    type Err = String;
+
+    fn from_str(value: &str) -> std::result::Result<Self, Self::Err> {
+        match value {
+            "table" => Ok(ReportFormat::Table),
+            "json" => Ok(ReportFormat::Json),
+            "csv" => Ok(ReportFormat::Csv),
+            _ => Err(format!("unknown format {:?}: expected table, json or csv", value)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ProfitStats {
+    pub count: usize,
+    pub mean_profit_usd: Decimal,
+    pub median_profit_usd: Decimal,
+    pub p95_profit_usd: Decimal,
+    pub total_profit_usd: Decimal,
+}
+
+impl ProfitStats {
+    // `profits` must be sorted ascending
+    fn from_sorted(profits: &[Decimal]) -> Self {
+        if profits.is_empty() {
+            return Self::default();
+        }
+
+        let count = profits.len();
+        let total: Decimal = profits.iter().sum();
+        let median = if count.is_multiple_of(2) {
+            (profits[count / 2 - 1] + profits[count / 2]) / Decimal::TWO
+        } else {
+            profits[count / 2]
+        };
+        // Nearest-rank percentile: the smallest value with at least 95% of samples at or below it
+        let p95 = profits[(count * 95).div_ceil(100) - 1];
+
+        Self {
+            count,
+            mean_profit_usd: (total / Decimal::from(count)).round_dp(8),
+            median_profit_usd: median,
+            p95_profit_usd: p95,
+            total_profit_usd: total,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PairStats {
+    pub token_pair: String,
+    pub stats: ProfitStats,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RouteStats {
+    pub token_pair: String,
+    pub buy_dex: String,
+    pub sell_dex: String,
+    pub stats: ProfitStats,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct HourCount {
+    pub hour: u32,
+    pub opportunities: i64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct OpportunityReport {
+    pub from: DateTime<Utc>,
+    pub to: DateTime<Utc>,
+    pub total: ProfitStats,
+    pub pairs: Vec<PairStats>,
+    // Most frequent first
+    pub routes: Vec<RouteStats>,
+    // UTC hour of day, all 24 hours
+    pub hours: Vec<HourCount>,
+}
+
+pub async fn build_report(database: &Database, from: DateTime<Utc>, to: DateTime<Utc>) -> Result<OpportunityReport> {
+    let route_profits = database.get_route_profits(from, to).await?;
+    let hourly = database.count_opportunities_by_hour(from, to).await?;
+
+    let mut all: Vec<Decimal> = route_profits.iter().flat_map(|route| route.profits.iter().copied()).collect();
+    all.sort();
+
+    // Routes arrive grouped by pair, so consecutive routes share a pair
+    let mut pairs: Vec<(String, Vec<Decimal>)> = Vec::new();
+    for route in &route_profits {
+        match pairs.last_mut() {
+            Some((token_pair, profits)) if *token_pair == route.token_pair => profits.extend(&route.profits),
+            _ => pairs.push((route.token_pair.clone(), route.profits.clone())),
+        }
+    }
+
+    let pairs = pairs
+        .into_iter()
+        .map(|(token_pair, mut profits)| {
+            profits.sort();
+            PairStats { token_pair, stats: ProfitStats::from_sorted(&profits) }
+        })
+        .collect();
+
+    let mut routes: Vec<RouteStats> = route_profits
+        .into_iter()
+        .map(|RouteProfits { token_pair, buy_dex, sell_dex, profits }| RouteStats {
+            token_pair,
+            buy_dex,
+            sell_dex,
+            stats: ProfitStats::from_sorted(&profits),
+        })
+        .collect();
+    // Stable sort keeps pair/route name order among equally frequent routes
+    routes.sort_by_key(|route| std::cmp::Reverse(route.stats.count));
+
+    let hours = (0..24)
+        .map(|hour| HourCount {
+            hour,
+            opportunities: hourly.iter().find(|(h, _)| *h == hour).map_or(0, |(_, count)| *count),
+        })
+        .collect();
+
+    Ok(OpportunityReport { from, to, total: ProfitStats::from_sorted(&all), pairs, routes, hours })
+}
+
+impl OpportunityReport {
+    pub fn render(&self, format: ReportFormat) -> Result<String> {
+        match format {
+            ReportFormat::Table => Ok(self.to_table()),
+            ReportFormat::Json => Ok(serde_json::to_string_pretty(self)?),
+            ReportFormat::Csv => self.to_csv(),
+        }
+    }
+
+    fn to_table(&self) -> String {
+        let mut out = String::new();
+        let row = |label: &str, stats: &ProfitStats| {
+            format!(
+                "{:<36} {:>7} {:>12.4} {:>12.4} {:>12.4} {:>14.2}\n",
+                label, stats.count, stats.mean_profit_usd, stats.median_profit_usd, stats.p95_profit_usd, stats.total_profit_usd
+            )
+        };
+        let header = |label: &str| {
+            format!("{:<36} {:>7} {:>12} {:>12} {:>12} {:>14}\n", label, "count", "mean $", "median $", "p95 $", "total $")
+        };
+
+        let _ = writeln!(out, "Opportunities {} .. {}\n", self.from, self.to);
+        out.push_str(&header("Pair"));
+        for pair in &self.pairs {
+            out.push_str(&row(&pair.token_pair, &pair.stats));
+        }
+        out.push_str(&row("Total", &self.total));
+
+        out.push('\n');
+        out.push_str(&header("Route (most frequent first)"));
+        for route in &self.routes {
+            out.push_str(&row(&format!("{} {} -> {}", route.token_pair, route.buy_dex, route.sell_dex), &route.stats));
+        }
+
+        out.push_str("\nHour (UTC)  opportunities\n");
+        let busiest = self.hours.iter().map(|hour| hour.opportunities).max().unwrap_or(0).max(1);
+        for hour in &self.hours {
+            let bar = "#".repeat((hour.opportunities * 40 / busiest) as usize);
+            let _ = writeln!(out, "{:02}:00 {:>19} {}", hour.hour, hour.opportunities, bar);
+        }
+
+        out
+    }
+
+    // One flat table; `section` says which columns apply to a row
+    fn to_csv(&self) -> Result<String> {
+        let mut writer = csv::Writer::from_writer(Vec::new());
+        writer.write_record([
+            "section", "token_pair", "buy_dex", "sell_dex", "hour", "count",
+            "mean_profit_usd", "median_profit_usd", "p95_profit_usd", "total_profit_usd",
+        ])?;
+
+        let stats_record = |section: &str, token_pair: &str, buy_dex: &str, sell_dex: &str, stats: &ProfitStats| {
+            vec![
+                section.to_string(),
+                token_pair.to_string(),
+                buy_dex.to_string(),
+                sell_dex.to_string(),
+                String::new(),
+                stats.count.to_string(),
+                stats.mean_profit_usd.to_string(),
+                stats.median_profit_usd.to_string(),
+                stats.p95_profit_usd.to_string(),
+                stats.total_profit_usd.to_string(),
+            ]
+        };
+
+        writer.write_record(stats_record("total", "", "", "", &self.total))?;
+        for pair in &self.pairs {
+            writer.write_record(stats_record("pair", &pair.token_pair, "", "", &pair.stats))?;
+        }
+        for route in &self.routes {
+            writer.write_record(stats_record("route", &route.token_pair, &route.buy_dex, &route.sell_dex, &route.stats))?;
+        }
+        for hour in &self.hours {
+            writer.write_record([
+                "hour".to_string(), String::new(), String::new(), String::new(),
+                hour.hour.to_string(), hour.opportunities.to_string(),
+                String::new(), String::new(), String::new(), String::new(),
+            ])?;
+        }
+
+        Ok(String::from_utf8(writer.into_inner()?)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    fn decimals(values: &[&str]) -> Vec<Decimal> {
+        values.iter().map(|value| Decimal::from_str(value).unwrap()).collect()
+    }
+
+    #[test]
+    fn profit_stats_use_median_and_nearest_rank_p95() {
+        let profits: Vec<Decimal> = (1..=20).map(Decimal::from).collect();
+        let stats = ProfitStats::from_sorted(&profits);
+        assert_eq!(stats.count, 20);
+        assert_eq!(stats.total_profit_usd, Decimal::from(210));
+        assert_eq!(stats.mean_profit_usd, Decimal::from_str("10.5").unwrap());
+        assert_eq!(stats.median_profit_usd, Decimal::from_str("10.5").unwrap());
+        assert_eq!(stats.p95_profit_usd, Decimal::from(19));
+
+        let stats = ProfitStats::from_sorted(&decimals(&["0.1", "0.2", "7"]));
+        assert_eq!(stats.median_profit_usd, Decimal::from_str("0.2").unwrap());
+        assert_eq!(stats.p95_profit_usd, Decimal::from(7));
+
+        assert_eq!(ProfitStats::from_sorted(&[]).count, 0);
+    }
+}
@@ -0,0 +1,54 @@
+use crate::config::RetentionConfig;
+use crate::database::Database;
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use std::time::{Duration, Instant};
+use tokio::time;
+use tracing::{info, warn};
+
+// Applies the retention policy on its own schedule so pruning never delays a detection cycle
+pub async fn run(database: Database, config: RetentionConfig) {
+    let mut interval = time::interval(Duration::from_secs(config.interval_secs.max(60)));
+    let mut last_vacuum: Option<Instant> = None;
+
+    loop {
+        interval.tick().await;
+
+        match apply(&database, &config, Utc::now()).await {
+            Ok(0) => {}
+            Ok(deleted) => info!("Retention pruned {} opportunities", deleted),
+            Err(e) => {
+                warn!("Failed to apply opportunity retention: {}", e);
+                continue;
+            }
+        }
+
+        let vacuum_interval = Duration::from_secs(config.vacuum_interval_hours * 3600);
+        if config.vacuum_interval_hours > 0 && last_vacuum.is_none_or(|last| last.elapsed() >= vacuum_interval) {
+            match database.vacuum().await {
+                Ok(()) => info!("Vacuumed database"),
+                Err(e) => warn!("Failed to vacuum database: {}", e),
+            }
+            last_vacuum = Some(Instant::now());
+        }
+    }
+}
+
+// Prunes whatever is older than the stricter of the age and row limits
+pub async fn apply(database: &Database, config: &RetentionConfig, now: DateTime<Utc>) -> Result<u64> {
+    let mut cutoff: Option<DateTime<Utc>> = None;
+
+    if config.max_age_days > 0 {
+        cutoff = Some(now - chrono::Duration::days(config.max_age_days as i64));
+    }
+    if config.max_rows > 0 {
+        if let Some(row_cutoff) = database.opportunity_row_cutoff(config.max_rows).await? {
+            cutoff = Some(cutoff.map_or(row_cutoff, |cutoff| cutoff.max(row_cutoff)));
+        }
+    }
+
+    match cutoff {
+        Some(cutoff) => database.prune_opportunities(cutoff, config.downsample).await,
+        None => Ok(0),
+    }
+}
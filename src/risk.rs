@@ -0,0 +1,292 @@
+use crate::config::RiskConfig;
+use crate::database::{ArbitrageOpportunity, Database};
+use anyhow::Result;
+use chrono::{Duration, Utc};
+use rust_decimal::Decimal;
+use std::fmt;
+use tracing::{error, info};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum RiskViolation {
+    KillSwitchActive(String),
+    MaxNotional { notional: Decimal, limit: Decimal },
+    MaxDailyLoss { loss: Decimal, limit: Decimal },
+    MaxTradesPerHour { trades: i64, limit: u32 },
+    MaxConsecutiveReverts { reverts: u32 },
+    MaxTokenExposure { token: String, exposure: Decimal, limit: Decimal },
+}
+
+impl fmt::Display for RiskViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::KillSwitchActive(reason) => write!(f, "kill switch active: {}", reason),
+            Self::MaxNotional { notional, limit } => write!(f, "trade notional ${} exceeds limit ${}", notional, limit),
+            Self::MaxDailyLoss { loss, limit } => write!(f, "daily loss ${} exceeds limit ${}", loss, limit),
+            Self::MaxTradesPerHour { trades, limit } => write!(f, "{} trades in the last hour, limit {}", trades, limit),
+            Self::MaxConsecutiveReverts { reverts } => write!(f, "{} consecutive reverted executions", reverts),
+            Self::MaxTokenExposure { token, exposure, limit } => {
+                write!(f, "{} exposure {} exceeds limit {}", token, exposure, limit)
+            }
+        }
+    }
+}
+
+// Gate for trade execution. All state it needs is read back from the
+// database, so limits and a tripped kill switch survive restarts. Detection
+// keeps running while the kill switch is active; only execution is halted.
+// Per-trade limits (notional, trade rate) only reject the trade at hand; the
+// limits on accumulated losses from executions latch the kill switch.
+pub struct RiskManager {
+    config: RiskConfig,
+}
+
+impl RiskManager {
+    pub fn new(config: RiskConfig) -> Self {
+        Self { config }
+    }
+
+    pub async fn pre_trade_check(&self, database: &Database, opportunity: &ArbitrageOpportunity) -> Result<Option<RiskViolation>> {
+        if !self.config.enabled {
+            return Ok(None);
+        }
+
+        if let Some((reason, _)) = database.get_kill_switch().await? {
+            return Ok(Some(RiskViolation::KillSwitchActive(reason)));
+        }
+
+        let limit = to_decimal(self.config.max_notional_usd);
+        if opportunity.trade_amount > limit {
+            return Ok(Some(RiskViolation::MaxNotional { notional: opportunity.trade_amount, limit }));
+        }
+
+        let trades = database.count_executions_since(Utc::now() - Duration::hours(1)).await?;
+        if trades >= self.config.max_trades_per_hour as i64 {
+            return Ok(Some(RiskViolation::MaxTradesPerHour { trades, limit: self.config.max_trades_per_hour }));
+        }
+
+        self.post_trade_check(database).await
+    }
+
+    // Limits driven by execution results; run after every recorded execution
+    pub async fn post_trade_check(&self, database: &Database) -> Result<Option<RiskViolation>> {
+        if !self.config.enabled {
+            return Ok(None);
+        }
+
+        let start_of_day = Utc::now().date_naive().and_hms_opt(0, 0, 0).unwrap().and_utc();
+        let loss = -database.get_realised_pnl_since(start_of_day).await?;
+        let limit = to_decimal(self.config.max_daily_loss_usd);
+        if loss > limit {
+            return self.trip(database, RiskViolation::MaxDailyLoss { loss, limit }).await;
+        }
+
+        let max_reverts = self.config.max_consecutive_reverts;
+        if max_reverts > 0 {
+            let statuses = database.get_recent_execution_statuses(max_reverts as i64).await?;
+            if statuses.len() == max_reverts as usize && statuses.iter().all(|status| status != "success") {
+                return self.trip(database, RiskViolation::MaxConsecutiveReverts { reverts: max_reverts }).await;
+            }
+        }
+
+        // Leftovers from before the last reset were flattened by the operator
+        let since = database.get_risk_reset_at().await?;
+        for (token, exposure) in database.get_token_residuals_since(since).await? {
+            if let Some(limit) = self.config.max_token_exposure.get(&token).copied().map(to_decimal) {
+                if exposure.abs() > limit {
+                    return self.trip(database, RiskViolation::MaxTokenExposure { token, exposure, limit }).await;
+                }
+            }
+        }
+
+        Ok(None)
+    }
+
+    pub async fn reset(&self, database: &Database) -> Result<()> {
+        database.clear_kill_switch().await?;
+        info!("Risk kill switch reset, execution re-enabled");
+        Ok(())
+    }
+
+    async fn trip(&self, database: &Database, violation: RiskViolation) -> Result<Option<RiskViolation>> {
+        database.set_kill_switch(&violation.to_string()).await?;
+        error!("Risk limit tripped, halting execution: {}", violation);
+        Ok(Some(violation))
+    }
+}
+
+fn to_decimal(value: f64) -> Decimal {
+    Decimal::try_from(value).unwrap_or(Decimal::ZERO)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::tests::{opportunity, temp_database, temp_database_url};
+    use crate::database::Execution;
+    use std::str::FromStr;
+
+    fn trade(trade_amount: &str) -> ArbitrageOpportunity {
+        ArbitrageOpportunity {
+            trade_amount: Decimal::from_str(trade_amount).unwrap(),
+            ..opportunity("WETH/USDC", "QuickSwap", "5", Utc::now())
+        }
+    }
+
+    async fn record_execution(database: &Database, status: &str, realised_pnl_usd: &str, base_residual: &str) {
+        let opportunity_id = database.save_opportunity(&trade("1000")).await.unwrap();
+        database
+            .save_execution(&Execution {
+                id: None,
+                opportunity_id,
+                timestamp: Utc::now(),
+                tx_hashes: vec!["0x01".to_string()],
+                block_number: Some(1),
+                status: status.to_string(),
+                quote_token: "USDC".to_string(),
+                quote_spent: Decimal::ONE_THOUSAND,
+                quote_received: Decimal::ONE_THOUSAND,
+                base_token: "WETH".to_string(),
+                base_residual: Decimal::from_str(base_residual).unwrap(),
+                swap_count: 2,
+                gas_used: 300_000,
+                gas_cost_native: Decimal::ZERO,
+                gas_cost_usd: Decimal::ZERO,
+                realised_pnl_usd: Decimal::from_str(realised_pnl_usd).unwrap(),
+            })
+            .await
+            .unwrap();
+    }
+
+    fn kill_switch_active(violation: Option<RiskViolation>) -> bool {
+        matches!(violation, Some(RiskViolation::KillSwitchActive(_)))
+    }
+
+    #[tokio::test]
+    async fn max_notional_rejects_the_trade_only() {
+        let database = temp_database("risk-notional").await;
+        let risk = RiskManager::new(RiskConfig::default());
+
+        let violation = risk.pre_trade_check(&database, &trade("10000.01")).await.unwrap();
+        assert!(matches!(violation, Some(RiskViolation::MaxNotional { .. })));
+        assert!(database.get_kill_switch().await.unwrap().is_none());
+        assert_eq!(risk.pre_trade_check(&database, &trade("10000")).await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn max_trades_per_hour_rejects_the_trade_only() {
+        let database = temp_database("risk-trade-rate").await;
+        let risk = RiskManager::new(RiskConfig { max_trades_per_hour: 2, ..RiskConfig::default() });
+
+        record_execution(&database, "success", "1", "0").await;
+        assert_eq!(risk.pre_trade_check(&database, &trade("1000")).await.unwrap(), None);
+
+        record_execution(&database, "success", "1", "0").await;
+        let violation = risk.pre_trade_check(&database, &trade("1000")).await.unwrap();
+        assert_eq!(violation, Some(RiskViolation::MaxTradesPerHour { trades: 2, limit: 2 }));
+        assert!(database.get_kill_switch().await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn max_daily_loss_trips_the_kill_switch() {
+        let database = temp_database("risk-daily-loss").await;
+        let risk = RiskManager::new(RiskConfig::default());
+
+        record_execution(&database, "success", "-60", "0").await;
+        assert_eq!(risk.post_trade_check(&database).await.unwrap(), None);
+
+        record_execution(&database, "success", "-40.01", "0").await;
+        let violation = risk.post_trade_check(&database).await.unwrap();
+        assert!(matches!(violation, Some(RiskViolation::MaxDailyLoss { .. })));
+        assert!(database.get_kill_switch().await.unwrap().is_some());
+        assert!(kill_switch_active(risk.pre_trade_check(&database, &trade("1")).await.unwrap()));
+    }
+
+    #[tokio::test]
+    async fn consecutive_reverts_trip_the_kill_switch() {
+        let database = temp_database("risk-reverts").await;
+        let risk = RiskManager::new(RiskConfig::default());
+
+        record_execution(&database, "reverted", "0", "0").await;
+        record_execution(&database, "reverted", "0", "0").await;
+        record_execution(&database, "success", "0", "0").await;
+        record_execution(&database, "reverted", "0", "0").await;
+        assert_eq!(risk.post_trade_check(&database).await.unwrap(), None);
+
+        record_execution(&database, "reverted", "0", "0").await;
+        assert_eq!(risk.post_trade_check(&database).await.unwrap(), None);
+        record_execution(&database, "reverted", "0", "0").await;
+        let violation = risk.post_trade_check(&database).await.unwrap();
+        assert_eq!(violation, Some(RiskViolation::MaxConsecutiveReverts { reverts: 3 }));
+        assert!(database.get_kill_switch().await.unwrap().is_some());
+    }
+
+    #[tokio::test]
+    async fn token_exposure_trips_the_kill_switch() {
+        let database = temp_database("risk-exposure").await;
+        let mut config = RiskConfig::default();
+        config.max_token_exposure.insert("WETH".to_string(), 0.5);
+        let risk = RiskManager::new(config);
+
+        record_execution(&database, "success", "0", "0.3").await;
+        assert_eq!(risk.post_trade_check(&database).await.unwrap(), None);
+
+        record_execution(&database, "success", "0", "-0.9").await;
+        let violation = risk.post_trade_check(&database).await.unwrap();
+        assert!(matches!(violation, Some(RiskViolation::MaxTokenExposure { ref token, .. }) if token == "WETH"));
+        assert!(database.get_kill_switch().await.unwrap().is_some());
+    }
+
+    #[tokio::test]
+    async fn flattening_and_reset_clear_a_token_exposure_trip() {
+        let database = temp_database("risk-exposure-reset").await;
+        let mut config = RiskConfig::default();
+        config.max_token_exposure.insert("WETH".to_string(), 0.5);
+        let risk = RiskManager::new(config);
+
+        record_execution(&database, "success", "0", "0.9").await;
+        assert!(matches!(risk.post_trade_check(&database).await.unwrap(), Some(RiskViolation::MaxTokenExposure { .. })));
+
+        // The operator sells the leftover WETH, then resets
+        risk.reset(&database).await.unwrap();
+        assert_eq!(risk.pre_trade_check(&database, &trade("1")).await.unwrap(), None);
+        assert!(database.get_kill_switch().await.unwrap().is_none());
+
+        // Leftovers from executions after the reset count again
+        record_execution(&database, "success", "0", "0.3").await;
+        assert_eq!(risk.post_trade_check(&database).await.unwrap(), None);
+        record_execution(&database, "success", "0", "0.3").await;
+        assert!(matches!(risk.post_trade_check(&database).await.unwrap(), Some(RiskViolation::MaxTokenExposure { .. })));
+    }
+
+    #[tokio::test]
+    async fn kill_switch_survives_a_restart_until_reset() {
+        let url = temp_database_url("risk-restart");
+        let database = Database::new(&url).await.unwrap();
+        database.migrate().await.unwrap();
+        record_execution(&database, "success", "-150", "0").await;
+        let risk = RiskManager::new(RiskConfig::default());
+        assert!(risk.post_trade_check(&database).await.unwrap().is_some());
+        drop(database);
+
+        let database = Database::new(&url).await.unwrap();
+        database.migrate().await.unwrap();
+        let risk = RiskManager::new(RiskConfig::default());
+        assert!(kill_switch_active(risk.pre_trade_check(&database, &trade("1")).await.unwrap()));
+
+        // Raising the limit alone does not release the switch; resetting does
+        let risk = RiskManager::new(RiskConfig { max_daily_loss_usd: 1_000.0, ..RiskConfig::default() });
+        assert!(kill_switch_active(risk.pre_trade_check(&database, &trade("1")).await.unwrap()));
+        risk.reset(&database).await.unwrap();
+        assert!(database.get_kill_switch().await.unwrap().is_none());
+        assert_eq!(risk.pre_trade_check(&database, &trade("1")).await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn disabled_risk_checks_allow_everything() {
+        let database = temp_database("risk-disabled").await;
+        let risk = RiskManager::new(RiskConfig { enabled: false, ..RiskConfig::default() });
+
+        database.set_kill_switch("manual").await.unwrap();
+        assert_eq!(risk.pre_trade_check(&database, &trade("1000000")).await.unwrap(), None);
+    }
+}
@@ -0,0 +1,347 @@
+use crate::config::{PolygonConfig, RpcMode};
+use crate::errors::{ArbitrageError, Result};
+use crate::metrics::METRICS;
+use async_trait::async_trait;
+use ethers::providers::{Http, HttpClientError, JsonRpcClient, JsonRpcError, Provider, ProviderError, RpcError};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::{HashMap, VecDeque};
+use std::fmt::Debug;
+use std::fs::{self, File};
+use std::io::Write;
+use std::str::FromStr;
+use std::sync::{Arc, LazyLock, Mutex};
+use std::time::Instant;
+use thiserror::Error;
+
+pub type RpcProvider = Provider<RpcTransport>;
+
+// Cassettes being recorded by this process, keyed by path. Each is truncated
+// when first opened; later providers for the same path append to that handle.
+static RECORDING_CASSETTES: LazyLock<Mutex<HashMap<String, Arc<Mutex<File>>>>> = LazyLock::new(Default::default);
+
+// One JSON-RPC exchange as written to the cassette file (one per line)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CassetteEntry {
+    method: String,
+    params: Value,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    result: Option<Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    error: Option<Value>,
+}
+
+#[derive(Debug, Error)]
+pub enum RpcTransportError {
+    #[error(transparent)]
+    Http(#[from] HttpClientError),
+
+    #[error(transparent)]
+    JsonRpc(#[from] JsonRpcError),
+
+    #[error("Deserialization error: {0}")]
+    SerdeJson(#[from] serde_json::Error),
+
+    #[error("Cassette error: {0}")]
+    Cassette(String),
+}
+
+impl RpcError for RpcTransportError {
+    fn as_error_response(&self) -> Option<&JsonRpcError> {
+        match self {
+            Self::Http(e) => e.as_error_response(),
+            Self::JsonRpc(e) => Some(e),
+            _ => None,
+        }
+    }
+
+    fn as_serde_error(&self) -> Option<&serde_json::Error> {
+        match self {
+            Self::Http(e) => e.as_serde_error(),
+            Self::SerdeJson(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<RpcTransportError> for ProviderError {
+    fn from(src: RpcTransportError) -> Self {
+        match src {
+            RpcTransportError::Http(e) => e.into(),
+            other => ProviderError::JsonRpcClientError(Box::new(other)),
+        }
+    }
+}
+
+// JSON-RPC transport that can talk to the node directly, record every
+// exchange to a cassette file while doing so, or serve a cassette back
+// without any network access.
+#[derive(Debug)]
+pub enum RpcTransport {
+    Live(Http),
+    Recording {
+        inner: Http,
+        cassette: Arc<Mutex<File>>,
+    },
+    Replay {
+        responses: Mutex<HashMap<String, VecDeque<CassetteEntry>>>,
+    },
+}
+
+impl RpcTransport {
+    pub fn from_config(config: &PolygonConfig) -> Result<Self> {
+        let cassette_path = || {
+            config
+                .rpc_cassette
+                .clone()
+                .ok_or_else(|| ArbitrageError::Config("polygon.rpc_cassette is required to record or replay".to_string()))
+        };
+
+        match config.rpc_mode {
+            RpcMode::Live => Ok(Self::Live(Self::http(&config.rpc_url)?)),
+            RpcMode::Record => {
+                let path = cassette_path()?;
+                let mut cassettes = RECORDING_CASSETTES.lock().unwrap();
+                let cassette = match cassettes.get(&path) {
+                    Some(cassette) => cassette.clone(),
+                    None => {
+                        let file = File::create(&path)
+                            .map_err(|e| ArbitrageError::Config(format!("Failed to open cassette {}: {}", path, e)))?;
+                        let cassette = Arc::new(Mutex::new(file));
+                        cassettes.insert(path, cassette.clone());
+                        cassette
+                    }
+                };
+                Ok(Self::Recording {
+                    inner: Self::http(&config.rpc_url)?,
+                    cassette,
+                })
+            }
+            RpcMode::Replay => Self::replay(&cassette_path()?),
+        }
+    }
+
+    pub fn replay(path: &str) -> Result<Self> {
+        let content = fs::read_to_string(path)
+            .map_err(|e| ArbitrageError::Config(format!("Failed to read cassette {}: {}", path, e)))?;
+
+        let mut responses: HashMap<String, VecDeque<CassetteEntry>> = HashMap::new();
+        for (index, line) in content.lines().enumerate().filter(|(_, line)| !line.trim().is_empty()) {
+            let entry: CassetteEntry = serde_json::from_str(line)
+                .map_err(|e| ArbitrageError::Config(format!("Invalid cassette entry at {}:{}: {}", path, index + 1, e)))?;
+            responses
+                .entry(request_key(&entry.method, &entry.params))
+                .or_default()
+                .push_back(entry);
+        }
+
+        Ok(Self::Replay {
+            responses: Mutex::new(responses),
+        })
+    }
+
+    fn http(url: &str) -> Result<Http> {
+        Http::from_str(url).map_err(|e| ArbitrageError::Config(format!("Invalid RPC URL {}: {}", url, e)))
+    }
+
+    fn next_replayed(&self, method: &str, params: &Value) -> std::result::Result<Value, RpcTransportError> {
+        let Self::Replay { responses } = self else {
+            unreachable!("only called in replay mode");
+        };
+
+        let key = request_key(method, params);
+        let mut responses = responses.lock().unwrap();
+        let queue = responses
+            .get_mut(&key)
+            .ok_or_else(|| RpcTransportError::Cassette(format!("No recorded response for {}", key)))?;
+
+        // Responses are served once each, in recorded order. Running out is an
+        // error rather than a repeat, so a replay never outlives its recording
+        let entry = queue
+            .pop_front()
+            .ok_or_else(|| RpcTransportError::Cassette(format!("Recorded responses exhausted for {}", key)))?;
+
+        match (entry.result, entry.error) {
+            (_, Some(error)) => Err(RpcTransportError::JsonRpc(serde_json::from_value(error)?)),
+            (Some(result), None) => Ok(result),
+            (None, None) => Ok(Value::Null),
+        }
+    }
+
+    fn record(&self, entry: &CassetteEntry) -> std::result::Result<(), RpcTransportError> {
+        let Self::Recording { cassette, .. } = self else {
+            return Ok(());
+        };
+
+        let mut line = serde_json::to_string(entry)?;
+        line.push('\n');
+        cassette
+            .lock()
+            .unwrap()
+            .write_all(line.as_bytes())
+            .map_err(|e| RpcTransportError::Cassette(format!("Failed to write cassette: {}", e)))
+    }
+}
+
+#[async_trait]
+impl JsonRpcClient for RpcTransport {
+    type Error = RpcTransportError;
+
+    async fn request<T, R>(&self, method: &str, params: T) -> std::result::Result<R, Self::Error>
+    where
+        T: Debug + Serialize + Send + Sync,
+        R: DeserializeOwned + Send,
+    {
+        let started = Instant::now();
+        let response = self.dispatch(method, params).await;
+        let result = if response.is_ok() { "ok" } else { "error" };
+        METRICS.rpc_latency.observe_duration(&[method, result], started.elapsed());
+        response
+    }
+}
+
+impl RpcTransport {
+    async fn dispatch<T, R>(&self, method: &str, params: T) -> std::result::Result<R, RpcTransportError>
+    where
+        T: Debug + Serialize + Send + Sync,
+        R: DeserializeOwned + Send,
+    {
+        match self {
+            Self::Live(inner) => Ok(inner.request(method, params).await?),
+            Self::Recording { inner, .. } => {
+                let params = serde_json::to_value(&params)?;
+                let response: std::result::Result<Value, HttpClientError> = inner.request(method, &params).await;
+
+                let (result, error) = match &response {
+                    Ok(value) => (Some(value.clone()), None),
+                    Err(HttpClientError::JsonRpcError(e)) => (
+                        None,
+                        Some(json!({ "code": e.code, "message": e.message, "data": e.data })),
+                    ),
+                    // Transport failures are not part of the node's behaviour; don't record them
+                    Err(_) => (None, None),
+                };
+                if result.is_some() || error.is_some() {
+                    self.record(&CassetteEntry {
+                        method: method.to_string(),
+                        params,
+                        result,
+                        error,
+                    })?;
+                }
+
+                Ok(serde_json::from_value(response?)?)
+            }
+            Self::Replay { .. } => {
+                let params = serde_json::to_value(&params)?;
+                Ok(serde_json::from_value(self.next_replayed(method, &params)?)?)
+            }
+        }
+    }
+}
+
+fn request_key(method: &str, params: &Value) -> String {
+    format!("{} {}", method, params)
+}
+
+pub fn connect(config: &PolygonConfig) -> Result<Arc<RpcProvider>> {
+    Ok(Arc::new(Provider::new(RpcTransport::from_config(config)?)))
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::arbitrage::{token_pairs, ArbitrageDetector};
+    use crate::config::tests::test_config;
+    use crate::database::tests::temp_database_url;
+    use crate::database::Database;
+    use crate::dex::DexManager;
+    use ethers::providers::Middleware;
+    use rust_decimal::Decimal;
+
+    const CASSETTE: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/fixtures/rpc_cassette.jsonl");
+
+    fn replay_config() -> crate::config::Config {
+        let mut config = test_config();
+        config.polygon.rpc_mode = RpcMode::Replay;
+        config.polygon.rpc_cassette = Some(CASSETTE.to_string());
+        config
+    }
+
+    #[tokio::test]
+    async fn replay_serves_each_response_once() {
+        let provider = connect(&replay_config().polygon).unwrap();
+
+        // The cassette holds one head block per pair quoted
+        assert_eq!(provider.get_block_number().await.unwrap().as_u64(), 50_000_000);
+        assert_eq!(provider.get_block_number().await.unwrap().as_u64(), 50_000_000);
+        let exhausted = provider.get_block_number().await.unwrap_err();
+        assert!(exhausted.to_string().contains("exhausted"), "{}", exhausted);
+    }
+
+    #[test]
+    fn recording_starts_a_fresh_cassette() {
+        let path = std::env::temp_dir().join(format!("arbitrage-record-{}.jsonl", std::process::id()));
+        fs::write(&path, "{\"method\":\"eth_chainId\",\"params\":[],\"result\":\"0x1\"}\n").unwrap();
+        let mut config = test_config();
+        config.polygon.rpc_mode = RpcMode::Record;
+        config.polygon.rpc_cassette = Some(path.display().to_string());
+
+        // Every provider of one run shares the cassette instead of truncating it again
+        let first = RpcTransport::from_config(&config.polygon).unwrap();
+        let second = RpcTransport::from_config(&config.polygon).unwrap();
+        for (transport, block) in [(&first, "0x1"), (&second, "0x2")] {
+            transport
+                .record(&CassetteEntry {
+                    method: "eth_blockNumber".to_string(),
+                    params: json!([]),
+                    result: Some(json!(block)),
+                    error: None,
+                })
+                .unwrap();
+        }
+
+        let recorded = fs::read_to_string(&path).unwrap();
+        assert_eq!(recorded.lines().count(), 2);
+        assert!(!recorded.contains("eth_chainId"));
+        let _ = fs::remove_file(&path);
+    }
+
+    #[tokio::test]
+    async fn dex_manager_quotes_from_replay() {
+        let manager = DexManager::new(replay_config()).await.unwrap();
+        let weth_usdc = &token_pairs(&replay_config())[0];
+
+        let quotes: Vec<(String, Decimal)> = manager
+            .get_all_prices(weth_usdc)
+            .await
+            .into_iter()
+            .map(|quote| (quote.dex_name, quote.price))
+            .collect();
+        assert_eq!(
+            quotes,
+            vec![("Uniswap V2".to_string(), Decimal::new(1800, 0)), ("QuickSwap".to_string(), Decimal::new(18155, 1))]
+        );
+    }
+
+    #[tokio::test]
+    async fn once_finds_the_recorded_spread_offline() {
+        let url = temp_database_url("replay-once");
+        let database = Database::new(&url).await.unwrap();
+        database.migrate().await.unwrap();
+        let mut detector = ArbitrageDetector::new(replay_config(), Database::new(&url).await.unwrap()).await.unwrap();
+
+        let opportunities = detector.check_arbitrage_opportunities().await.unwrap();
+        let routes: Vec<(&str, &str, &str)> = opportunities
+            .iter()
+            .map(|o| (o.token_pair.as_str(), o.buy_dex.as_str(), o.sell_dex.as_str()))
+            .collect();
+        assert_eq!(routes, vec![("WETH/USDC", "Uniswap V2", "QuickSwap")]);
+        assert_eq!(opportunities[0].price_difference, Decimal::new(155, 1));
+        assert_eq!(database.get_recent_opportunities(10).await.unwrap().len(), 1);
+
+        // A second cycle has nothing left to replay, so no quotes and no opportunities
+        assert!(detector.check_arbitrage_opportunities().await.unwrap().is_empty());
+    }
+}
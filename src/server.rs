@@ -0,0 +1,55 @@
+use crate::config::ServerConfig;
+use crate::health::HealthChecks;
+use crate::metrics::METRICS;
+use anyhow::{Context, Result};
+use hyper::header::CONTENT_TYPE;
+use hyper::service::{make_service_fn, service_fn};
+use hyper::{Body, Method, Request, Response, Server, StatusCode};
+use std::convert::Infallible;
+use std::future::Future;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use tracing::{error, info};
+
+// Binds up front so a taken port fails startup instead of a background task
+pub fn bind(config: &ServerConfig, checks: HealthChecks) -> Result<impl Future<Output = ()>> {
+    let address: SocketAddr = config.listen.parse()
+        .with_context(|| format!("server.listen {:?} is not a socket address", config.listen))?;
+    let builder = Server::try_bind(&address).with_context(|| format!("cannot listen on {}", address))?;
+
+    let checks = Arc::new(checks);
+    let service = make_service_fn(move |_| {
+        let checks = checks.clone();
+        async move {
+            Ok::<_, Infallible>(service_fn(move |request| handle(checks.clone(), request)))
+        }
+    });
+    let server = builder.serve(service);
+    info!("Serving /metrics, /healthz and /readyz on http://{}", server.local_addr());
+
+    Ok(async move {
+        if let Err(e) = server.await {
+            error!("HTTP server stopped: {}", e);
+        }
+    })
+}
+
+async fn handle(checks: Arc<HealthChecks>, request: Request<Body>) -> std::result::Result<Response<Body>, Infallible> {
+    let response = match (request.method(), request.uri().path()) {
+        (&Method::GET, "/metrics") => Response::builder()
+            .header(CONTENT_TYPE, "text/plain; version=0.0.4")
+            .body(Body::from(METRICS.render())),
+        (&Method::GET, "/healthz") => probe(checks.liveness()),
+        (&Method::GET, "/readyz") => probe(checks.readiness().await),
+        _ => Response::builder().status(StatusCode::NOT_FOUND).body(Body::from("not found\n")),
+    };
+    Ok(response.expect("static response parts are valid"))
+}
+
+// 200 when the probe passes and 503 otherwise, with the details as JSON either way
+fn probe((ok, body): (bool, serde_json::Value)) -> hyper::http::Result<Response<Body>> {
+    Response::builder()
+        .status(if ok { StatusCode::OK } else { StatusCode::SERVICE_UNAVAILABLE })
+        .header(CONTENT_TYPE, "application/json")
+        .body(Body::from(body.to_string()))
+}
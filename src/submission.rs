@@ -0,0 +1,279 @@
+use crate::config::{Config, SubmissionBackend};
+use crate::errors::{ArbitrageError, Result};
+use ethers::types::{Bytes, H256, U64};
+use ethers::utils::keccak256;
+use serde_json::{json, Value};
+use std::time::Duration;
+use tracing::info;
+
+#[derive(Debug, Clone)]
+pub struct SubmissionOutcome {
+    pub backend: SubmissionBackend,
+    pub tx_hashes: Vec<H256>,
+    pub bundle_hash: Option<String>,
+    pub target_block: Option<u64>,
+}
+
+// Sends already-signed transactions through the backend selected in config.
+// Every backend speaks plain JSON-RPC, so pointing the endpoint at a local
+// mock relay is enough to exercise it without touching the network.
+pub struct TxSubmitter {
+    client: reqwest::Client,
+    backend: SubmissionBackend,
+    rpc_url: String,
+    endpoint: String,
+    bundle_target_blocks: u64,
+}
+
+impl TxSubmitter {
+    pub fn new(config: &Config) -> Result<Self> {
+        let submission = &config.submission;
+
+        let endpoint = match submission.backend {
+            SubmissionBackend::Public => config.polygon.rpc_url.clone(),
+            SubmissionBackend::PrivateRpc => submission.private_rpc_url.clone().ok_or_else(|| {
+                ArbitrageError::Config("submission.private_rpc_url is required for the private_rpc backend".to_string())
+            })?,
+            SubmissionBackend::Bundle => submission.relay_url.clone().ok_or_else(|| {
+                ArbitrageError::Config("submission.relay_url is required for the bundle backend".to_string())
+            })?,
+        };
+
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(submission.timeout_secs))
+            .build()?;
+
+        Ok(Self {
+            client,
+            backend: submission.backend,
+            rpc_url: config.polygon.rpc_url.clone(),
+            endpoint,
+            bundle_target_blocks: submission.bundle_target_blocks.max(1),
+        })
+    }
+
+    pub async fn submit(&self, signed_txs: &[Bytes]) -> Result<SubmissionOutcome> {
+        if signed_txs.is_empty() {
+            return Err(ArbitrageError::Submission("No transactions to submit".to_string()));
+        }
+
+        match self.backend {
+            SubmissionBackend::Public | SubmissionBackend::PrivateRpc => {
+                let mut tx_hashes = Vec::with_capacity(signed_txs.len());
+                for tx in signed_txs {
+                    let result = self
+                        .rpc_call(&self.endpoint, "eth_sendRawTransaction", json!([tx]))
+                        .await?;
+                    let hash: H256 = serde_json::from_value(result)
+                        .map_err(|e| ArbitrageError::Submission(format!("Invalid transaction hash: {}", e)))?;
+                    tx_hashes.push(hash);
+                }
+
+                info!("Submitted {} transaction(s) via {:?}", tx_hashes.len(), self.backend);
+
+                Ok(SubmissionOutcome {
+                    backend: self.backend,
+                    tx_hashes,
+                    bundle_hash: None,
+                    target_block: None,
+                })
+            }
+            SubmissionBackend::Bundle => self.submit_bundle(signed_txs).await,
+        }
+    }
+
+    async fn submit_bundle(&self, signed_txs: &[Bytes]) -> Result<SubmissionOutcome> {
+        let current_block: U64 = serde_json::from_value(
+            self.rpc_call(&self.rpc_url, "eth_blockNumber", json!([])).await?,
+        )
+        .map_err(|e| ArbitrageError::Submission(format!("Invalid block number: {}", e)))?;
+        let target_block = current_block.as_u64() + self.bundle_target_blocks;
+
+        let result = self
+            .rpc_call(
+                &self.endpoint,
+                "eth_sendBundle",
+                json!([{
+                    "txs": signed_txs,
+                    "blockNumber": format!("0x{:x}", target_block),
+                }]),
+            )
+            .await?;
+
+        let bundle_hash = result
+            .get("bundleHash")
+            .and_then(Value::as_str)
+            .map(str::to_string);
+
+        // Relays only acknowledge the bundle, so derive the hashes locally
+        let tx_hashes = signed_txs
+            .iter()
+            .map(|tx| H256::from(keccak256(tx)))
+            .collect();
+
+        info!("Submitted bundle of {} transaction(s) targeting block {}", signed_txs.len(), target_block);
+
+        Ok(SubmissionOutcome {
+            backend: self.backend,
+            tx_hashes,
+            bundle_hash,
+            target_block: Some(target_block),
+        })
+    }
+
+    async fn rpc_call(&self, url: &str, method: &str, params: Value) -> Result<Value> {
+        let request = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": method,
+            "params": params,
+        });
+
+        let response: Value = self
+            .client
+            .post(url)
+            .json(&request)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+
+        if let Some(error) = response.get("error") {
+            return Err(ArbitrageError::Submission(format!("{} failed: {}", method, error)));
+        }
+
+        response
+            .get("result")
+            .cloned()
+            .ok_or_else(|| ArbitrageError::Submission(format!("{} returned no result", method)))
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::config::tests::test_config;
+    use hyper::service::{make_service_fn, service_fn};
+    use hyper::{Body, Request, Response, Server};
+    use std::convert::Infallible;
+    use std::net::SocketAddr;
+    use std::sync::{Arc, Mutex};
+
+    pub(crate) type Requests = Arc<Mutex<Vec<Value>>>;
+
+    // JSON-RPC relay on a local port that records each request body and
+    // answers with whatever `respond` returns for it
+    pub(crate) fn mock_relay(respond: fn(&Value) -> Value) -> (String, Requests) {
+        let requests: Requests = Arc::default();
+        let recorded = requests.clone();
+        let service = make_service_fn(move |_| {
+            let recorded = recorded.clone();
+            async move {
+                Ok::<_, Infallible>(service_fn(move |request: Request<Body>| {
+                    let recorded = recorded.clone();
+                    async move {
+                        let body = hyper::body::to_bytes(request.into_body()).await.unwrap();
+                        let request: Value = serde_json::from_slice(&body).unwrap();
+                        let response = respond(&request);
+                        recorded.lock().unwrap().push(request);
+                        Ok::<_, Infallible>(Response::new(Body::from(response.to_string())))
+                    }
+                }))
+            }
+        });
+
+        let server = Server::bind(&SocketAddr::from(([127, 0, 0, 1], 0))).serve(service);
+        let url = format!("http://{}", server.local_addr());
+        tokio::spawn(server);
+        (url, requests)
+    }
+
+    fn relay_response(request: &Value) -> Value {
+        let result = match request["method"].as_str().unwrap() {
+            "eth_blockNumber" => json!("0x2faf080"),
+            "eth_sendBundle" => json!({ "bundleHash": "0xb0b0" }),
+            "eth_sendRawTransaction" => json!(format!("{:?}", H256::from(keccak256(Bytes::from(vec![0x02, 0x01]))))),
+            method => panic!("unexpected {}", method),
+        };
+        json!({ "jsonrpc": "2.0", "id": request["id"], "result": result })
+    }
+
+    fn submitter(backend: SubmissionBackend, url: &str) -> TxSubmitter {
+        let mut config = test_config();
+        config.polygon.rpc_url = url.to_string();
+        config.submission.backend = backend;
+        config.submission.private_rpc_url = Some(url.to_string());
+        config.submission.relay_url = Some(url.to_string());
+        TxSubmitter::new(&config).unwrap()
+    }
+
+    #[tokio::test]
+    async fn bundles_target_a_future_block() {
+        let (url, requests) = mock_relay(relay_response);
+        let txs = vec![Bytes::from(vec![0x02, 0x01]), Bytes::from(vec![0x02, 0x02])];
+
+        let outcome = submitter(SubmissionBackend::Bundle, &url).submit(&txs).await.unwrap();
+
+        let requests = requests.lock().unwrap();
+        assert_eq!(requests.len(), 2);
+        assert_eq!(requests[0]["method"], "eth_blockNumber");
+        assert_eq!(requests[1]["jsonrpc"], "2.0");
+        assert_eq!(requests[1]["method"], "eth_sendBundle");
+        // 50,000,000 plus the default two blocks ahead
+        assert_eq!(requests[1]["params"], json!([{ "txs": ["0x0201", "0x0202"], "blockNumber": "0x2faf082" }]));
+
+        assert_eq!(outcome.target_block, Some(50_000_002));
+        assert_eq!(outcome.bundle_hash.as_deref(), Some("0xb0b0"));
+        assert_eq!(outcome.tx_hashes, txs.iter().map(|tx| H256::from(keccak256(tx))).collect::<Vec<_>>());
+    }
+
+    #[tokio::test]
+    async fn raw_transactions_are_sent_one_by_one() {
+        let (url, requests) = mock_relay(relay_response);
+        let tx = Bytes::from(vec![0x02, 0x01]);
+
+        let outcome = submitter(SubmissionBackend::PrivateRpc, &url).submit(std::slice::from_ref(&tx)).await.unwrap();
+
+        let requests = requests.lock().unwrap();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0]["method"], "eth_sendRawTransaction");
+        assert_eq!(requests[0]["params"], json!(["0x0201"]));
+        assert_eq!(outcome.tx_hashes, vec![H256::from(keccak256(&tx))]);
+        assert_eq!(outcome.target_block, None);
+    }
+
+    #[tokio::test]
+    async fn rejected_raw_transactions_fail_the_submission() {
+        let (url, _) = mock_relay(|request| {
+            json!({ "jsonrpc": "2.0", "id": request["id"], "error": { "code": -32000, "message": "nonce too low" } })
+        });
+
+        let error = submitter(SubmissionBackend::PrivateRpc, &url)
+            .submit(&[Bytes::from(vec![0x02])])
+            .await
+            .unwrap_err();
+        assert!(matches!(error, ArbitrageError::Submission(ref message) if message.contains("nonce too low")), "{}", error);
+    }
+
+    #[tokio::test]
+    async fn rejected_bundles_fail_the_submission() {
+        let (url, requests) = mock_relay(|request| match request["method"].as_str().unwrap() {
+            "eth_sendBundle" => {
+                json!({ "jsonrpc": "2.0", "id": request["id"], "error": { "code": -32000, "message": "bundle rejected" } })
+            }
+            _ => relay_response(request),
+        });
+
+        let error = submitter(SubmissionBackend::Bundle, &url)
+            .submit(&[Bytes::from(vec![0x02])])
+            .await
+            .unwrap_err();
+        assert!(matches!(error, ArbitrageError::Submission(ref message) if message.contains("bundle rejected")), "{}", error);
+        assert_eq!(requests.lock().unwrap().len(), 2);
+
+        // Nothing is sent for an empty bundle
+        assert!(submitter(SubmissionBackend::Bundle, &url).submit(&[]).await.is_err());
+        assert_eq!(requests.lock().unwrap().len(), 2);
+    }
+}
@@ -0,0 +1,541 @@
+use crate::arbitrage::token_pairs;
+use crate::config::{Config, DexConfig, PriceScript, RpcMode, SubmissionBackend, TokenConfig};
+use crate::dex::get_erc20_abi;
+use crate::errors::{ArbitrageError, Result};
+use crate::logging;
+use crate::rpc::{self, RpcProvider};
+use ethers::{
+    contract::Contract,
+    providers::Middleware,
+    types::Address,
+    utils::to_checksum,
+};
+use std::collections::HashSet;
+use std::fmt;
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::Arc;
+
+// rust_decimal cannot represent more fractional digits than this
+const MAX_TOKEN_DECIMALS: u8 = 28;
+
+#[derive(Debug, Clone)]
+pub struct ConfigIssue {
+    pub field: String,
+    pub message: String,
+}
+
+// Every problem found in one pass, so a bad config can be fixed in one edit
+#[derive(Debug, Default)]
+pub struct ValidationReport {
+    pub issues: Vec<ConfigIssue>,
+}
+
+impl ValidationReport {
+    fn error(&mut self, field: impl Into<String>, message: impl Into<String>) {
+        self.issues.push(ConfigIssue { field: field.into(), message: message.into() });
+    }
+
+    pub fn into_result(self) -> Result<()> {
+        if self.issues.is_empty() {
+            Ok(())
+        } else {
+            Err(ArbitrageError::Config(self.to_string()))
+        }
+    }
+}
+
+impl fmt::Display for ValidationReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} problem(s) found", self.issues.len())?;
+        for issue in &self.issues {
+            write!(f, "\n  - {}: {}", issue.field, issue.message)?;
+        }
+        Ok(())
+    }
+}
+
+fn chain_dexes(config: &Config) -> [(&'static str, &DexConfig); 3] {
+    [
+        ("uniswap_v2", &config.dexes.uniswap_v2),
+        ("quickswap", &config.dexes.quickswap),
+        ("sushiswap", &config.dexes.sushiswap),
+    ]
+}
+
+fn tokens(config: &Config) -> [(&'static str, &TokenConfig); 3] {
+    [
+        ("weth", &config.tokens.weth),
+        ("wbtc", &config.tokens.wbtc),
+        ("usdc", &config.tokens.usdc),
+    ]
+}
+
+// Accepts all-lowercase or all-uppercase hex; mixed case must be a valid EIP-55 checksum
+fn check_address(report: &mut ValidationReport, field: &str, value: &str) -> Option<Address> {
+    let hex = value.strip_prefix("0x").unwrap_or("");
+    if hex.len() != 40 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        report.error(field, format!("{:?} is not a 0x-prefixed 20-byte hex address", value));
+        return None;
+    }
+
+    let address = Address::from_str(value).ok()?;
+    let mixed_case = hex.chars().any(|c| c.is_ascii_lowercase()) && hex.chars().any(|c| c.is_ascii_uppercase());
+    let checksummed = to_checksum(&address, None);
+    if mixed_case && checksummed != value {
+        report.error(field, format!("{} fails its EIP-55 checksum (did you mean {}?)", value, checksummed));
+    }
+
+    Some(address)
+}
+
+fn check_positive(report: &mut ValidationReport, field: &str, value: f64) {
+    if !value.is_finite() || value <= 0.0 {
+        report.error(field, format!("must be greater than 0, got {}", value));
+    }
+}
+
+fn check_non_negative(report: &mut ValidationReport, field: &str, value: f64) {
+    if !value.is_finite() || value < 0.0 {
+        report.error(field, format!("must be 0 or more, got {}", value));
+    }
+}
+
+fn check_url(report: &mut ValidationReport, field: &str, value: &str) {
+    let schemes = ["http://", "https://", "ws://", "wss://"];
+    if !schemes.iter().any(|scheme| value.starts_with(scheme)) || value.len() <= "http://".len() {
+        report.error(field, format!("{:?} is not an http(s) or ws(s) URL", value));
+    }
+}
+
+// Checks that need nothing but the config file itself
+pub fn validate(config: &Config) -> ValidationReport {
+    let mut report = ValidationReport::default();
+
+    if config.general.check_interval == 0 {
+        report.error("general.check_interval", "must be at least 1 second");
+    }
+    if let Err(e) = logging::parse_level(&config.general.log_level) {
+        report.error("general.log_level", format!("{}; use trace, debug, info, warn or error, optionally per module as target=level", e));
+    }
+    if let Some(file) = &config.general.log_file {
+        if file.path.trim().is_empty() {
+            report.error("general.log_file.path", "must not be empty");
+        }
+    }
+
+    let polygon = &config.polygon;
+    check_url(&mut report, "polygon.rpc_url", &polygon.rpc_url);
+    if polygon.chain_id == 0 {
+        report.error("polygon.chain_id", "must be set (137 for Polygon mainnet)");
+    }
+    check_positive(&mut report, "polygon.gas_price_gwei", polygon.gas_price_gwei);
+    if polygon.gas_limit == 0 {
+        report.error("polygon.gas_limit", "must be greater than 0");
+    }
+    check_positive(&mut report, "polygon.native_price_usd", polygon.native_price_usd);
+    if polygon.rpc_mode != RpcMode::Live && polygon.rpc_cassette.is_none() {
+        report.error("polygon.rpc_cassette", "is required when rpc_mode is record or replay");
+    }
+    if polygon.rpc_mode == RpcMode::Replay {
+        if let Some(path) = &polygon.rpc_cassette {
+            if !Path::new(path).exists() {
+                report.error("polygon.rpc_cassette", format!("{} does not exist; record it first with rpc_mode = \"record\"", path));
+            }
+        }
+    }
+
+    let mut dex_names = HashSet::new();
+    for (key, dex) in chain_dexes(config) {
+        if dex.name.trim().is_empty() {
+            report.error(format!("dexes.{}.name", key), "must not be empty");
+        }
+        if !dex.enabled {
+            continue;
+        }
+        if !dex_names.insert(dex.name.to_lowercase()) {
+            report.error(format!("dexes.{}.name", key), format!("{:?} is used by another enabled DEX", dex.name));
+        }
+        check_address(&mut report, &format!("dexes.{}.router_address", key), &dex.router_address);
+        check_address(&mut report, &format!("dexes.{}.factory_address", key), &dex.factory_address);
+    }
+
+    let pair_names: Vec<String> = token_pairs(config)
+        .iter()
+        .map(|pair| format!("{}/{}", pair.token0.symbol, pair.token1.symbol))
+        .collect();
+    for (index, dex) in config.synthetic_dexes.iter().enumerate() {
+        let field = format!("synthetic_dexes[{}]", index);
+        if dex.name.trim().is_empty() {
+            report.error(format!("{}.name", field), "must not be empty");
+        }
+        if dex.enabled && !dex_names.insert(dex.name.to_lowercase()) {
+            report.error(format!("{}.name", field), format!("{:?} is used by another enabled DEX", dex.name));
+        }
+        check_positive(&mut report, &format!("{}.liquidity", field), dex.liquidity);
+
+        for (pair, script) in &dex.pairs {
+            let field = format!("{}.pairs.{:?}", field, pair);
+            if !pair_names.contains(pair) {
+                report.error(&field, format!("unknown pair; configured pairs are {}", pair_names.join(", ")));
+            }
+            match script {
+                PriceScript::Csv { path } => {
+                    if !Path::new(path).exists() {
+                        report.error(format!("{}.path", field), format!("{} does not exist", path));
+                    }
+                }
+                PriceScript::RandomWalk { .. } | PriceScript::Static { .. } | PriceScript::Spikes { .. } => {
+                    check_positive(&mut report, &format!("{}.price", field), script.initial_price());
+                }
+            }
+        }
+    }
+
+    let mut symbols = HashSet::new();
+    let mut addresses = HashSet::new();
+    for (key, token) in tokens(config) {
+        if token.symbol.trim().is_empty() {
+            report.error(format!("tokens.{}.symbol", key), "must not be empty");
+        } else if !symbols.insert(token.symbol.to_uppercase()) {
+            report.error(format!("tokens.{}.symbol", key), format!("{:?} is used by another token", token.symbol));
+        }
+        if token.decimals > MAX_TOKEN_DECIMALS {
+            report.error(format!("tokens.{}.decimals", key), format!("{} is more than the supported {}", token.decimals, MAX_TOKEN_DECIMALS));
+        }
+        if let Some(address) = check_address(&mut report, &format!("tokens.{}.address", key), &token.address) {
+            if !addresses.insert(address) {
+                report.error(format!("tokens.{}.address", key), "is the same contract as another token");
+            }
+        }
+    }
+
+    let url = &config.database.url;
+    if !(url.starts_with("sqlite:") || url.starts_with("postgres://") || url.starts_with("postgresql://")) {
+        report.error("database.url", format!("{:?} must start with sqlite:, postgres:// or postgresql://", url));
+    }
+
+    let arbitrage = &config.arbitrage;
+    check_non_negative(&mut report, "arbitrage.min_profit_usd", arbitrage.min_profit_usd);
+    check_non_negative(&mut report, "arbitrage.min_profit_percentage", arbitrage.min_profit_percentage);
+    check_positive(&mut report, "arbitrage.trade_amount_usd", arbitrage.trade_amount_usd);
+    check_non_negative(&mut report, "arbitrage.max_slippage", arbitrage.max_slippage);
+    check_non_negative(&mut report, "arbitrage.min_liquidity", arbitrage.min_liquidity);
+
+    let submission = &config.submission;
+    match submission.backend {
+        SubmissionBackend::PrivateRpc if submission.private_rpc_url.is_none() => {
+            report.error("submission.private_rpc_url", "is required for the private_rpc backend");
+        }
+        SubmissionBackend::Bundle if submission.relay_url.is_none() => {
+            report.error("submission.relay_url", "is required for the bundle backend");
+        }
+        _ => {}
+    }
+    if let Some(url) = &submission.private_rpc_url {
+        check_url(&mut report, "submission.private_rpc_url", url);
+    }
+    if let Some(url) = &submission.relay_url {
+        check_url(&mut report, "submission.relay_url", url);
+    }
+    if submission.timeout_secs == 0 {
+        report.error("submission.timeout_secs", "must be at least 1 second");
+    }
+
+    if let Some(wallet) = &config.wallet {
+        check_address(&mut report, "wallet.address", &wallet.address);
+        if let Some(key) = &wallet.private_key {
+            let hex = key.trim_start_matches("0x");
+            if hex.len() != 64 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+                // Never echo the key itself
+                report.error("wallet.private_key", "must be 32 bytes of hex");
+            }
+        }
+        check_non_negative(&mut report, "wallet.min_native_balance", wallet.min_native_balance);
+        if wallet.snapshot_interval == 0 {
+            report.error("wallet.snapshot_interval", "must be at least 1 second");
+        }
+    }
+
+    if config.server.enabled && config.server.listen.parse::<std::net::SocketAddr>().is_err() {
+        report.error("server.listen", format!("{:?} is not an address:port such as 0.0.0.0:8080", config.server.listen));
+    }
+
+    let health = &config.health;
+    if health.max_cycle_age_secs > 0 && health.max_cycle_age_secs < config.general.check_interval {
+        report.error(
+            "health.max_cycle_age_secs",
+            format!("{} is shorter than general.check_interval ({}), so every probe would fail", health.max_cycle_age_secs, config.general.check_interval),
+        );
+    }
+    if health.max_quote_age_secs > 0 && health.max_quote_age_secs < config.general.check_interval {
+        report.error(
+            "health.max_quote_age_secs",
+            format!("{} is shorter than general.check_interval ({}), so every probe would fail", health.max_quote_age_secs, config.general.check_interval),
+        );
+    }
+    if health.check_timeout_secs == 0 {
+        report.error("health.check_timeout_secs", "must be at least 1 second");
+    }
+
+    let risk = &config.risk;
+    check_non_negative(&mut report, "risk.max_notional_usd", risk.max_notional_usd);
+    check_non_negative(&mut report, "risk.max_daily_loss_usd", risk.max_daily_loss_usd);
+    for (symbol, limit) in &risk.max_token_exposure {
+        let field = format!("risk.max_token_exposure.{}", symbol);
+        if !symbols.contains(&symbol.to_uppercase()) {
+            report.error(&field, "is not a configured token symbol");
+        }
+        check_non_negative(&mut report, &field, *limit);
+    }
+
+    report
+}
+
+// Checks the config against the chain it points at. Skipped in replay mode,
+// where only recorded calls can be answered, and when nothing uses the chain.
+pub async fn validate_on_chain(config: &Config, report: &mut ValidationReport) {
+    let uses_chain = chain_dexes(config).iter().any(|(_, dex)| dex.enabled) || config.wallet.is_some();
+    if !uses_chain || config.polygon.rpc_mode == RpcMode::Replay {
+        return;
+    }
+
+    let provider = match rpc::connect(&config.polygon) {
+        Ok(provider) => provider,
+        Err(e) => {
+            report.error("polygon.rpc_url", format!("cannot create RPC client: {}", e));
+            return;
+        }
+    };
+
+    match provider.get_chainid().await {
+        Ok(chain_id) if chain_id.as_u64() != config.polygon.chain_id => {
+            report.error(
+                "polygon.chain_id",
+                format!("is {} but {} is on chain {}", config.polygon.chain_id, config.polygon.rpc_url, chain_id),
+            );
+        }
+        Ok(_) => {}
+        Err(e) => {
+            // Nothing else can be checked without a working endpoint
+            report.error("polygon.rpc_url", format!("eth_chainId failed: {}", e));
+            return;
+        }
+    }
+
+    for (key, dex) in chain_dexes(config) {
+        if !dex.enabled {
+            continue;
+        }
+        for (field, value) in [("router_address", &dex.router_address), ("factory_address", &dex.factory_address)] {
+            if let Ok(address) = Address::from_str(value) {
+                check_has_code(&provider, report, &format!("dexes.{}.{}", key, field), address).await;
+            }
+        }
+    }
+
+    for (key, token) in tokens(config) {
+        let Ok(address) = Address::from_str(&token.address) else {
+            continue;
+        };
+        let field = format!("tokens.{}", key);
+        if !check_has_code(&provider, report, &format!("{}.address", field), address).await {
+            continue;
+        }
+
+        let contract = Contract::new(address, get_erc20_abi(), provider.clone());
+        match contract.method::<_, u8>("decimals", ()) {
+            Ok(call) => match call.call().await {
+                Ok(decimals) if decimals != token.decimals => {
+                    report.error(format!("{}.decimals", field), format!("is {} but the contract reports {}", token.decimals, decimals));
+                }
+                Ok(_) => {}
+                Err(e) => report.error(format!("{}.address", field), format!("decimals() failed: {}", e)),
+            },
+            Err(e) => report.error(format!("{}.address", field), format!("decimals() failed: {}", e)),
+        }
+        match contract.method::<_, String>("symbol", ()) {
+            Ok(call) => match call.call().await {
+                Ok(symbol) if !symbol.eq_ignore_ascii_case(&token.symbol) => {
+                    report.error(format!("{}.symbol", field), format!("is {:?} but the contract reports {:?}", token.symbol, symbol));
+                }
+                Ok(_) => {}
+                // Some older tokens return bytes32 symbols; a mismatch there is not worth failing startup
+                Err(_) => {}
+            },
+            Err(e) => report.error(format!("{}.address", field), format!("symbol() failed: {}", e)),
+        }
+    }
+}
+
+async fn check_has_code(provider: &Arc<RpcProvider>, report: &mut ValidationReport, field: &str, address: Address) -> bool {
+    match provider.get_code(address, None).await {
+        Ok(code) if code.as_ref().is_empty() => {
+            report.error(field, format!("no contract is deployed at {:?} on this chain", address));
+            false
+        }
+        Ok(_) => true,
+        Err(e) => {
+            report.error(field, format!("eth_getCode failed: {}", e));
+            false
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::tests::test_config;
+    use crate::submission::tests::mock_relay;
+    use ethers::abi::{encode, Token};
+    use serde_json::{json, Value};
+
+    fn fields(report: &ValidationReport) -> Vec<&str> {
+        report.issues.iter().map(|issue| issue.field.as_str()).collect()
+    }
+
+    #[test]
+    fn the_test_config_is_valid() {
+        assert!(validate(&test_config()).issues.is_empty());
+    }
+
+    #[test]
+    fn zero_check_interval_is_rejected() {
+        let mut config = test_config();
+        config.general.check_interval = 0;
+        assert_eq!(fields(&validate(&config)), vec!["general.check_interval"]);
+    }
+
+    #[test]
+    fn negative_thresholds_are_rejected() {
+        let mut config = test_config();
+        config.arbitrage.min_profit_usd = -1.0;
+        config.arbitrage.min_profit_percentage = -0.1;
+        config.arbitrage.min_liquidity = f64::NAN;
+        config.risk.max_daily_loss_usd = -5.0;
+
+        assert_eq!(
+            fields(&validate(&config)),
+            vec!["arbitrage.min_profit_usd", "arbitrage.min_profit_percentage", "arbitrage.min_liquidity", "risk.max_daily_loss_usd"]
+        );
+    }
+
+    #[test]
+    fn duplicate_dex_names_and_token_symbols_are_rejected() {
+        let mut config = test_config();
+        config.dexes.sushiswap.name = "quickswap".to_string();
+        config.tokens.wbtc.symbol = "weth".to_string();
+        assert_eq!(fields(&validate(&config)), vec!["dexes.sushiswap.name", "tokens.wbtc.symbol"]);
+
+        // A disabled DEX may share its name
+        config.dexes.sushiswap.enabled = false;
+        config.tokens.wbtc.symbol = "WBTC".to_string();
+        assert!(validate(&config).issues.is_empty());
+    }
+
+    #[test]
+    fn bundle_backend_needs_a_relay_url() {
+        let mut config = test_config();
+        config.submission.backend = SubmissionBackend::Bundle;
+        config.submission.relay_url = None;
+        assert_eq!(fields(&validate(&config)), vec!["submission.relay_url"]);
+
+        config.submission.relay_url = Some("https://relay.example".to_string());
+        assert!(validate(&config).issues.is_empty());
+    }
+
+    #[test]
+    fn health_ages_shorter_than_the_interval_are_rejected() {
+        let mut config = test_config();
+        config.health.max_cycle_age_secs = config.general.check_interval - 1;
+        config.health.max_quote_age_secs = config.general.check_interval - 1;
+        assert_eq!(fields(&validate(&config)), vec!["health.max_cycle_age_secs", "health.max_quote_age_secs"]);
+
+        config.health.max_cycle_age_secs = config.general.check_interval;
+        config.health.max_quote_age_secs = 0;
+        assert!(validate(&config).issues.is_empty());
+    }
+
+    #[test]
+    fn exposure_limits_must_name_a_configured_token() {
+        let mut config = test_config();
+        config.risk.max_token_exposure.insert("weth".to_string(), 2.0);
+        config.risk.max_token_exposure.insert("DAI".to_string(), 100.0);
+        assert_eq!(fields(&validate(&config)), vec!["risk.max_token_exposure.DAI"]);
+    }
+
+    // A node on chain 1 where the SushiSwap router has no code and every
+    // token claims to be an 18-decimal "WETH"
+    fn mismatched_node(request: &Value) -> Value {
+        let result = match request["method"].as_str().unwrap() {
+            "eth_chainId" => json!("0x1"),
+            "eth_getCode" if request["params"][0] == "0x1b02da8cb0d097eb8d57a175b88c7d8b47997506" => json!("0x"),
+            "eth_getCode" => json!("0x6080"),
+            "eth_call" => {
+                let data = request["params"][0]["data"].as_str().or(request["params"][0]["input"].as_str()).unwrap();
+                let output = match &data[..10] {
+                    "0x313ce567" => encode(&[Token::Uint(18.into())]),
+                    "0x95d89b41" => encode(&[Token::String("WETH".to_string())]),
+                    selector => panic!("unexpected call {}", selector),
+                };
+                json!(format!("0x{}", hex::encode(output)))
+            }
+            method => panic!("unexpected {}", method),
+        };
+        json!({ "jsonrpc": "2.0", "id": request["id"], "result": result })
+    }
+
+    #[tokio::test]
+    async fn on_chain_mismatches_are_reported() {
+        let (url, _) = mock_relay(mismatched_node);
+        let mut config = test_config();
+        config.polygon.rpc_url = url;
+        let mut report = ValidationReport::default();
+
+        validate_on_chain(&config, &mut report).await;
+        assert_eq!(
+            fields(&report),
+            vec![
+                "polygon.chain_id",
+                "dexes.sushiswap.router_address",
+                "tokens.wbtc.decimals",
+                "tokens.wbtc.symbol",
+                "tokens.usdc.decimals",
+                "tokens.usdc.symbol",
+            ]
+        );
+        assert!(report.issues[0].message.contains("chain 1"));
+    }
+
+    #[tokio::test]
+    async fn an_unreachable_node_stops_the_on_chain_checks() {
+        let (url, requests) = mock_relay(|request| {
+            json!({ "jsonrpc": "2.0", "id": request["id"], "error": { "code": -32601, "message": "method not found" } })
+        });
+        let mut config = test_config();
+        config.polygon.rpc_url = url;
+        let mut report = ValidationReport::default();
+
+        validate_on_chain(&config, &mut report).await;
+        assert_eq!(fields(&report), vec!["polygon.rpc_url"]);
+        assert_eq!(requests.lock().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn addresses_must_be_hex_and_correctly_checksummed() {
+        let mut report = ValidationReport::default();
+        let weth = "0x7ceB23fD6bC0adD59E62ac25578270cFf1b9f619";
+
+        assert!(check_address(&mut report, "lower", &weth.to_lowercase()).is_some());
+        assert!(check_address(&mut report, "checksummed", weth).is_some());
+        assert!(report.issues.is_empty());
+
+        check_address(&mut report, "bad_checksum", "0x7ceb23fD6bC0adD59E62ac25578270cFf1b9f619");
+        check_address(&mut report, "short", "0x7ceB23fD");
+        check_address(&mut report, "no_prefix", &weth[2..]);
+
+        let fields: Vec<&str> = report.issues.iter().map(|issue| issue.field.as_str()).collect();
+        assert_eq!(fields, vec!["bad_checksum", "short", "no_prefix"]);
+        assert!(report.issues[0].message.contains(weth));
+    }
+}
@@ -0,0 +1,244 @@
+use crate::config::{Config, TokenConfig, WalletConfig};
+use crate::dex::{get_erc20_abi, to_decimal_units};
+use crate::errors::{ArbitrageError, Result};
+use crate::rpc::RpcProvider;
+use chrono::{DateTime, Utc};
+use ethers::{
+    contract::Contract,
+    providers::Middleware,
+    signers::{LocalWallet, Signer},
+    types::{Address, U256},
+};
+use rust_decimal::Decimal;
+use std::str::FromStr;
+use std::sync::Arc;
+use tracing::warn;
+
+#[derive(Debug, Clone)]
+pub struct TokenAllowance {
+    pub dex_name: String,
+    pub amount: Decimal,
+}
+
+#[derive(Debug, Clone)]
+pub struct TokenBalance {
+    pub symbol: String,
+    pub address: String,
+    pub balance: Decimal,
+    pub allowances: Vec<TokenAllowance>,
+}
+
+#[derive(Debug, Clone)]
+pub struct WalletBalances {
+    pub timestamp: DateTime<Utc>,
+    pub wallet_address: String,
+    pub native_balance: Decimal,
+    pub tokens: Vec<TokenBalance>,
+}
+
+impl WalletBalances {
+    pub fn token(&self, symbol: &str) -> Option<&TokenBalance> {
+        self.tokens.iter().find(|token| token.symbol == symbol)
+    }
+
+    // Below the MATIC kept aside for gas; exactly at the threshold is enough
+    pub fn gas_is_low(&self, min_native_balance: f64) -> bool {
+        self.native_balance < Decimal::try_from(min_native_balance).unwrap_or(Decimal::ZERO)
+    }
+}
+
+pub struct Wallet {
+    provider: Arc<RpcProvider>,
+    address: Address,
+    config: WalletConfig,
+    tokens: Vec<TokenConfig>,
+    routers: Vec<(String, String)>,
+}
+
+impl Wallet {
+    pub fn new(provider: Arc<RpcProvider>, config: &Config, wallet_config: WalletConfig) -> Result<Self> {
+        let address = Address::from_str(&wallet_config.address)
+            .map_err(|e| ArbitrageError::Config(format!("Invalid wallet address: {}", e)))?;
+
+        let routers = config
+            .dexes
+            .all()
+            .into_iter()
+            .filter(|dex| dex.enabled)
+            .map(|dex| (dex.name.clone(), dex.router_address.clone()))
+            .collect();
+
+        Ok(Self {
+            provider,
+            address,
+            config: wallet_config,
+            tokens: config.tokens.all().into_iter().cloned().collect(),
+            routers,
+        })
+    }
+
+    // Same account and connection, with tokens and routers taken from a reloaded config
+    pub fn reconfigured(&self, config: &Config) -> Result<Self> {
+        Self::new(self.provider.clone(), config, self.config.clone())
+    }
+
+    pub fn config(&self) -> &WalletConfig {
+        &self.config
+    }
+
+    pub async fn fetch_balances(&self) -> Result<WalletBalances> {
+        let native = self.provider.get_balance(self.address, None).await?;
+        let native_balance = to_decimal_units(native, 18)?;
+
+        let mut tokens = Vec::with_capacity(self.tokens.len());
+        for token in &self.tokens {
+            tokens.push(self.fetch_token_balance(token).await?);
+        }
+
+        let balances = WalletBalances {
+            timestamp: Utc::now(),
+            wallet_address: format!("{:?}", self.address),
+            native_balance,
+            tokens,
+        };
+
+        self.check_gas_balance(&balances);
+
+        Ok(balances)
+    }
+
+    async fn fetch_token_balance(&self, token: &TokenConfig) -> Result<TokenBalance> {
+        let token_address = Address::from_str(&token.address)
+            .map_err(|e| ArbitrageError::Config(format!("Invalid {} address: {}", token.symbol, e)))?;
+        let contract = Contract::new(token_address, get_erc20_abi(), self.provider.clone());
+
+        let raw_balance: U256 = contract
+            .method::<_, U256>("balanceOf", self.address)
+            .map_err(|e| ArbitrageError::PriceFetch(format!("balanceOf encoding error: {}", e)))?
+            .call()
+            .await?;
+
+        let mut allowances = Vec::with_capacity(self.routers.len());
+        for (dex_name, router_address) in &self.routers {
+            let router = Address::from_str(router_address)
+                .map_err(|e| ArbitrageError::Config(format!("Invalid router address for {}: {}", dex_name, e)))?;
+            let raw_allowance: U256 = contract
+                .method::<_, U256>("allowance", (self.address, router))
+                .map_err(|e| ArbitrageError::PriceFetch(format!("allowance encoding error: {}", e)))?
+                .call()
+                .await?;
+
+            allowances.push(TokenAllowance {
+                dex_name: dex_name.clone(),
+                amount: to_decimal_units(raw_allowance, token.decimals)
+                    .unwrap_or(Decimal::MAX),
+            });
+        }
+
+        Ok(TokenBalance {
+            symbol: token.symbol.clone(),
+            address: token.address.clone(),
+            balance: to_decimal_units(raw_balance, token.decimals)?,
+            allowances,
+        })
+    }
+
+    fn check_gas_balance(&self, balances: &WalletBalances) {
+        if balances.gas_is_low(self.config.min_native_balance) {
+            warn!(
+                "Gas balance low for {}: {} MATIC (threshold {} MATIC)",
+                balances.wallet_address, balances.native_balance, self.config.min_native_balance
+            );
+        }
+    }
+}
+
+// Largest trade the wallet can fund when paying with `quote_token` through
+// `buy_dex`'s router, which can pull no more than its allowance
+pub fn cap_trade_amount(trade_amount: Decimal, balances: &WalletBalances, quote_token: &str, buy_dex: &str) -> Decimal {
+    let Some(token) = balances.token(quote_token) else {
+        return Decimal::ZERO;
+    };
+
+    let allowance = token
+        .allowances
+        .iter()
+        .find(|allowance| allowance.dex_name == buy_dex)
+        .map_or(Decimal::MAX, |allowance| allowance.amount);
+
+    trade_amount.min(token.balance).min(allowance)
+}
+
+// Signing key for the trading wallet, checked against the configured address
+pub fn load_signer(wallet_config: &WalletConfig, chain_id: u64) -> Result<LocalWallet> {
+    let private_key = wallet_config
+        .private_key
+        .as_deref()
+        .ok_or_else(|| ArbitrageError::Config("wallet.private_key is required to sign transactions".to_string()))?;
+
+    let signer = LocalWallet::from_str(private_key.trim_start_matches("0x"))
+        .map_err(|e| ArbitrageError::Config(format!("Invalid wallet private key: {}", e)))?
+        .with_chain_id(chain_id);
+
+    let expected = Address::from_str(&wallet_config.address)
+        .map_err(|e| ArbitrageError::Config(format!("Invalid wallet address: {}", e)))?;
+    if signer.address() != expected {
+        return Err(ArbitrageError::Config(format!(
+            "wallet.private_key belongs to {:?}, not {:?}",
+            signer.address(),
+            expected
+        )));
+    }
+
+    Ok(signer)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn usd(value: &str) -> Decimal {
+        Decimal::from_str(value).unwrap()
+    }
+
+    fn balances(native: &str, usdc: &str, allowances: &[(&str, &str)]) -> WalletBalances {
+        WalletBalances {
+            timestamp: Utc::now(),
+            wallet_address: "0x00000000000000000000000000000000000000aa".to_string(),
+            native_balance: usd(native),
+            tokens: vec![TokenBalance {
+                symbol: "USDC".to_string(),
+                address: "0x2791Bca1f2de4661ED88A30C99A7a9449Aa84174".to_string(),
+                balance: usd(usdc),
+                allowances: allowances
+                    .iter()
+                    .map(|(dex_name, amount)| TokenAllowance { dex_name: dex_name.to_string(), amount: usd(amount) })
+                    .collect(),
+            }],
+        }
+    }
+
+    #[test]
+    fn trades_are_capped_by_balance_and_router_allowance() {
+        let wallet = balances("10", "750", &[("QuickSwap", "400"), ("SushiSwap", "5000")]);
+
+        assert_eq!(cap_trade_amount(usd("1000"), &wallet, "USDC", "QuickSwap"), usd("400"));
+        assert_eq!(cap_trade_amount(usd("1000"), &wallet, "USDC", "SushiSwap"), usd("750"));
+        assert_eq!(cap_trade_amount(usd("300"), &wallet, "USDC", "SushiSwap"), usd("300"));
+        // No allowance recorded for the router: only the balance limits
+        assert_eq!(cap_trade_amount(usd("1000"), &wallet, "USDC", "Uniswap V2"), usd("750"));
+        // A token the wallet does not hold cannot fund anything
+        assert_eq!(cap_trade_amount(usd("1000"), &wallet, "DAI", "QuickSwap"), Decimal::ZERO);
+
+        let unapproved = balances("10", "750", &[("QuickSwap", "0")]);
+        assert_eq!(cap_trade_amount(usd("1000"), &unapproved, "USDC", "QuickSwap"), Decimal::ZERO);
+    }
+
+    #[test]
+    fn gas_is_low_only_below_the_threshold() {
+        assert!(balances("0.99", "0", &[]).gas_is_low(1.0));
+        assert!(!balances("1", "0", &[]).gas_is_low(1.0));
+        assert!(!balances("25", "0", &[]).gas_is_low(1.0));
+        assert!(!balances("0", "0", &[]).gas_is_low(0.0));
+    }
+}